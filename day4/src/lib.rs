@@ -1,3 +1,7 @@
+pub mod scoring;
+
+use scoring::{Scoring, ScoringRule, StandardScoring};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use thiserror::Error;
 
@@ -8,9 +12,13 @@ const BINGO_BOARD_SIZE: usize = 5;
 pub enum GameError {
     #[error("No draws left")]
     NoDrawsLeft,
+    #[error("Could not save or restore game state")]
+    SaveStateError(#[from] serde_json::Error),
+    #[error("Saved game uses a custom scoring rule, restore it with from_json_with_scoring")]
+    CustomScoring,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Player {
     board: [[u64; BINGO_BOARD_SIZE]; BINGO_BOARD_SIZE],
     // Vector of marked board indexes
@@ -38,13 +46,13 @@ impl Player {
 
     fn check_bingo(&self) -> bool {
         for row in 0..BINGO_BOARD_SIZE {
-            if self.check_bingo_row(row) == true {
+            if self.check_bingo_row(row) {
                 return true;
             }
         }
 
         for col in 0..BINGO_BOARD_SIZE {
-            if self.check_bingo_col(col) == true {
+            if self.check_bingo_col(col) {
                 return true;
             }
         }
@@ -60,6 +68,51 @@ impl Player {
         (0..BINGO_BOARD_SIZE).map(|row| (row, col)).all(|pair| self.marked.contains(&pair))
     }
 
+    fn check_bingo_diagonal(&self) -> bool {
+        (0..BINGO_BOARD_SIZE).map(|i| (i, i)).all(|pair| self.marked.contains(&pair))
+    }
+
+    fn check_bingo_antidiagonal(&self) -> bool {
+        (0..BINGO_BOARD_SIZE).map(|i| (i, BINGO_BOARD_SIZE-1-i)).all(|pair| self.marked.contains(&pair))
+    }
+
+    pub fn completed_rows(&self) -> usize {
+        (0..BINGO_BOARD_SIZE).filter(|row| self.check_bingo_row(*row)).count()
+    }
+
+    pub fn completed_cols(&self) -> usize {
+        (0..BINGO_BOARD_SIZE).filter(|col| self.check_bingo_col(*col)).count()
+    }
+
+    // Diagonals don't count as bingo, but scoring rules may reward them
+    pub fn completed_diagonals(&self) -> usize {
+        usize::from(self.check_bingo_diagonal()) + usize::from(self.check_bingo_antidiagonal())
+    }
+
+    pub fn sum_of_marked(&self) -> u64 {
+        let mut sum: u64 = 0;
+        for (i, row) in self.board.iter().enumerate() {
+            for (j, val) in row.iter().enumerate() {
+                if self.marked.contains(&(i,j)) {
+                    sum += val;
+                }
+            }
+        }
+        sum
+    }
+
+    pub fn count_of_unmarked(&self) -> u64 {
+        let mut count: u64 = 0;
+        for i in 0..BINGO_BOARD_SIZE {
+            for j in 0..BINGO_BOARD_SIZE {
+                if !self.marked.contains(&(i,j)) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
     pub fn sum_of_unmarked(&self) -> u64 {
         let mut sum: u64 = 0;
         for (i, row) in self.board.iter().enumerate() {
@@ -71,6 +124,14 @@ impl Player {
         }
         sum
     }
+
+    pub fn to_json(&self) -> Result<String, GameError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, GameError> {
+        Ok(serde_json::from_str(s)?)
+    }
 }
 
 impl std::str::FromStr for Player {
@@ -84,20 +145,72 @@ impl std::str::FromStr for Player {
     }
 }
 
+// Winners paired with their scores
+pub type ScoredWinners = Vec<(Player, u64)>;
+
 pub struct Game {
     players: Vec<Player>,
     draws: VecDeque<u64>,
+    turn: usize,
+    // Players that already won, together with their winning draw
+    finished: Vec<(u64, Player)>,
+    scoring: Box<dyn Scoring>,
+}
+
+// Saved form of a game, custom scoring rules are saved as null
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    players: Vec<Player>,
+    draws: VecDeque<u64>,
+    turn: usize,
+    finished: Vec<(u64, Player)>,
+    scoring: Option<ScoringRule>,
 }
 
 impl Game {
     pub fn new(draws: Vec<u64>) -> Self {
-        Game { players: vec![], draws: draws.into(), }
+        Game::with_scoring(draws, StandardScoring)
+    }
+
+    pub fn with_scoring(draws: Vec<u64>, scoring: impl Scoring + 'static) -> Self {
+        Game { players: vec![], draws: draws.into(), turn: 0, finished: vec![], scoring: Box::new(scoring), }
+    }
+
+    pub fn set_scoring(&mut self, scoring: impl Scoring + 'static) {
+        self.scoring = Box::new(scoring);
     }
 
     pub fn add_player(&mut self, player: Player) {
         self.players.push(player);
     }
 
+    pub fn finished_winners(&self) -> &[(u64, Player)] {
+        &self.finished
+    }
+
+    pub fn to_json(&self) -> Result<String, GameError> {
+        let saved = SavedGame {
+            players: self.players.clone(),
+            draws: self.draws.clone(),
+            turn: self.turn,
+            finished: self.finished.clone(),
+            scoring: self.scoring.rule(),
+        };
+        Ok(serde_json::to_string(&saved)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, GameError> {
+        let saved: SavedGame = serde_json::from_str(s)?;
+        let scoring: Box<dyn Scoring> = Box::new(saved.scoring.ok_or(GameError::CustomScoring)?);
+        Ok(Game { players: saved.players, draws: saved.draws, turn: saved.turn, finished: saved.finished, scoring })
+    }
+
+    // Restores a game with the given rule, needed for games saved with a custom one
+    pub fn from_json_with_scoring(s: &str, scoring: impl Scoring + 'static) -> Result<Self, GameError> {
+        let saved: SavedGame = serde_json::from_str(s)?;
+        Ok(Game { players: saved.players, draws: saved.draws, turn: saved.turn, finished: saved.finished, scoring: Box::new(scoring) })
+    }
+
     fn try_pop_winners(&mut self) -> Vec<Player> {
         let mut winners: Vec<Player> = vec![];
 
@@ -111,6 +224,7 @@ impl Game {
     pub fn get_next_winners(&mut self) -> Result<Option<(u64, Vec<Player>)>, GameError> {
         while !self.draws.is_empty() {
             let draw = self.draws.pop_front().ok_or(GameError::NoDrawsLeft)?;
+            self.turn += 1;
 
             for player in &mut self.players {
                 player.handle_new_draw(draw);
@@ -118,12 +232,28 @@ impl Game {
 
             let winners = self.try_pop_winners();
             if !winners.is_empty() {
+                self.finished.extend(winners.iter().cloned().map(|player| (draw, player)));
                 return Ok(Some((draw, winners)));
             }
 
         }
         Ok(None)
     }
+
+    pub fn get_next_scored_winners(&mut self) -> Result<Option<(u64, ScoredWinners)>, GameError> {
+        match self.get_next_winners()? {
+            Some((draw, winners)) => {
+                let scored = winners.into_iter()
+                    .map(|player| {
+                        let score = self.scoring.score(&player, draw, self.turn);
+                        (player, score)
+                    })
+                    .collect();
+                Ok(Some((draw, scored)))
+            },
+            None => Ok(None),
+        }
+    }
 }
 
 impl std::str::FromStr for Game {
@@ -189,19 +319,19 @@ mod tests {
                                       [1,  12, 20, 15, 19]]);
 
         player.handle_new_draw(8);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(2);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(23);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(4);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(24);
-        assert_eq!(player.check_bingo(), true);
+        assert!(player.check_bingo());
     }
 
     #[test]
@@ -213,19 +343,19 @@ mod tests {
                                       [1,  12, 20, 15, 19]]);
 
         player.handle_new_draw(17);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(23);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(14);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(3);
-        assert_eq!(player.check_bingo(), false);
+        assert!(!player.check_bingo());
 
         player.handle_new_draw(20);
-        assert_eq!(player.check_bingo(), true);
+        assert!(player.check_bingo());
     }
 
     #[test]
@@ -317,4 +447,137 @@ mod tests {
 
         let (_, _) = game.get_next_winners().unwrap().unwrap();
     }
+
+    #[test]
+    fn game_next_scored_winners() {
+        let mut game = Game::new(vec![7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1]);
+
+        game.add_player(Player::new([[22, 13, 17, 11, 0],
+                                     [8,  2,  23, 4,  24],
+                                     [21, 9,  14, 16, 7],
+                                     [6,  10, 3,  18, 5],
+                                     [1,  12, 20, 15, 19]]));
+
+        game.add_player(Player::new([[14, 21, 17, 24,  4],
+                                     [10, 16, 15,  9, 19],
+                                     [18,  8, 23, 26, 20],
+                                     [22, 11, 13,  6,  5],
+                                     [ 2,  0, 12,  3,  7]]));
+
+        let (winning_draw, winners) = game.get_next_scored_winners().unwrap().unwrap();
+        assert_eq!(winning_draw, 24);
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].1, 4512);
+
+        game.set_scoring(scoring::UnmarkedCount);
+        let (winning_draw, winners) = game.get_next_scored_winners().unwrap().unwrap();
+        assert_eq!(winners[0].1, winners[0].0.count_of_unmarked() * winning_draw);
+
+        assert!(game.get_next_scored_winners().unwrap().is_none());
+    }
+
+    fn example_game() -> Game {
+        let mut game = Game::new(vec![7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1]);
+
+        game.add_player(Player::new([[22, 13, 17, 11, 0],
+                                     [8,  2,  23, 4,  24],
+                                     [21, 9,  14, 16, 7],
+                                     [6,  10, 3,  18, 5],
+                                     [1,  12, 20, 15, 19]]));
+
+        game.add_player(Player::new([[3, 15,  0,  2, 22],
+                                     [9, 18, 13, 17,  5],
+                                     [19,  8,  7, 25, 23],
+                                     [20, 11, 10, 24,  4],
+                                     [14, 21, 16, 12,  6]]));
+
+        game.add_player(Player::new([[14, 21, 17, 24,  4],
+                                     [10, 16, 15,  9, 19],
+                                     [18,  8, 23, 26, 20],
+                                     [22, 11, 13,  6,  5],
+                                     [ 2,  0, 12,  3,  7]]));
+
+        game
+    }
+
+    #[test]
+    fn player_json_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut player = Player::new([[22, 13, 17, 11, 0],
+                                      [8,  2,  23, 4,  24],
+                                      [21, 9,  14, 16, 7],
+                                      [6,  10, 3,  18, 5],
+                                      [1,  12, 20, 15, 19]]);
+        player.handle_new_draw(23);
+        player.handle_new_draw(6);
+
+        let restored = Player::from_json(&player.to_json()?)?;
+        assert_eq!(restored, player);
+        assert_eq!(restored.marked, vec![(1,2), (3,0)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn game_json_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut game = example_game();
+        let (first_draw, _) = game.get_next_winners()?.unwrap();
+
+        let mut restored = Game::from_json(&game.to_json()?)?;
+        assert_eq!(restored.draws, game.draws);
+        assert_eq!(restored.players, game.players);
+        assert_eq!(restored.finished_winners(), game.finished_winners());
+        assert_eq!(restored.finished_winners()[0].0, first_draw);
+
+        while let Some((draw, winners)) = game.get_next_scored_winners()? {
+            let (restored_draw, restored_winners) = restored.get_next_scored_winners()?.unwrap();
+            assert_eq!(restored_draw, draw);
+            assert_eq!(restored_winners, winners);
+        }
+        assert!(restored.get_next_winners()?.is_none());
+        assert_eq!(restored.finished_winners().len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn game_json_roundtrip_scoring() -> Result<(), Box<dyn std::error::Error>> {
+        use scoring::{PatternWeighted, TurnBonus};
+
+        let mut game = example_game();
+        game.set_scoring(TurnBonus { base: PatternWeighted::default(), bonus_per_turn: 10, deadline: 15 });
+        game.get_next_winners()?;
+
+        let mut restored = Game::from_json(&game.to_json()?)?;
+        while let Some((draw, winners)) = game.get_next_scored_winners()? {
+            assert_eq!(restored.get_next_scored_winners()?, Some((draw, winners)));
+        }
+        assert!(restored.get_next_scored_winners()?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn game_json_custom_scoring() -> Result<(), Box<dyn std::error::Error>> {
+        struct DrawOnly;
+        impl Scoring for DrawOnly {
+            fn score(&self, _player: &Player, draw: u64, _turn: usize) -> u64 {
+                draw
+            }
+        }
+
+        let mut game = example_game();
+        game.set_scoring(DrawOnly);
+        let saved = game.to_json()?;
+        assert!(matches!(Game::from_json(&saved), Err(GameError::CustomScoring)));
+
+        let mut restored = Game::from_json_with_scoring(&saved, DrawOnly)?;
+        assert_eq!(restored.get_next_scored_winners()?, game.get_next_scored_winners()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn game_from_invalid_json() {
+        assert!(Game::from_json("{\"players\": 5}").is_err());
+    }
 }
@@ -9,7 +9,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>>{
 
     let file = File::open("inputfile").expect("Failed to find file with input data");
     let reader = BufReader::new(file);
-    for line in reader.lines().flatten() {
+    for line in reader.lines().map_while(Result::ok) {
         data.push(line.parse().unwrap());
     }
 
@@ -27,13 +27,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>>{
         game.add_player(FromStr::from_str(board.as_ref())?);
     }
 
-    let (winning_draw, winners) = game.get_next_winners().unwrap().unwrap();
+    let (winning_draw, winners) = game.get_next_scored_winners()?.unwrap();
     println!("Winning draw: {winning_draw}");
-    println!("Sum of first winner's unmarked numbers: {}", winners[0].sum_of_unmarked());
+    println!("First winner's score: {}", winners[0].1);
 
-    while let Some((next_winning_draw, next_winners)) = game.get_next_winners().unwrap() {
+    while let Some((next_winning_draw, next_winners)) = game.get_next_scored_winners()? {
         println!("Next winning draw: {next_winning_draw}");
-        println!("Sum of next winner's unmarked numbers: {}", next_winners[0].sum_of_unmarked());
+        for (_, score) in next_winners {
+            println!("Next winner's score: {score}");
+        }
     }
 
     Ok(())
@@ -0,0 +1,208 @@
+use crate::Player;
+use serde::{Deserialize, Serialize};
+
+pub trait Scoring {
+    // Turn is the number of draws made so far, starting from 1
+    fn score(&self, player: &Player, draw: u64, turn: usize) -> u64;
+
+    // Built-in rules describe themselves so saved games can restore them
+    fn rule(&self) -> Option<ScoringRule> {
+        None
+    }
+}
+
+// Any combination of the built-in rules, in a form that can be saved
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoringRule {
+    Standard,
+    MarkedSum,
+    UnmarkedCount,
+    PatternWeighted { row: u64, col: u64, diagonal: u64 },
+    TurnBonus { base: Box<ScoringRule>, bonus_per_turn: u64, deadline: usize },
+}
+
+impl Scoring for ScoringRule {
+    fn score(&self, player: &Player, draw: u64, turn: usize) -> u64 {
+        match self {
+            ScoringRule::Standard => StandardScoring.score(player, draw, turn),
+            ScoringRule::MarkedSum => MarkedSum.score(player, draw, turn),
+            ScoringRule::UnmarkedCount => UnmarkedCount.score(player, draw, turn),
+            ScoringRule::PatternWeighted { row, col, diagonal } => {
+                PatternWeighted { row: *row, col: *col, diagonal: *diagonal }.score(player, draw, turn)
+            }
+            ScoringRule::TurnBonus { base, bonus_per_turn, deadline } => {
+                TurnBonus { base: base.as_ref(), bonus_per_turn: *bonus_per_turn, deadline: *deadline }.score(player, draw, turn)
+            }
+        }
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        Some(self.clone())
+    }
+}
+
+impl<S: Scoring + ?Sized> Scoring for &S {
+    fn score(&self, player: &Player, draw: u64, turn: usize) -> u64 {
+        (**self).score(player, draw, turn)
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        (**self).rule()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StandardScoring;
+
+impl Scoring for StandardScoring {
+    fn score(&self, player: &Player, draw: u64, _turn: usize) -> u64 {
+        player.sum_of_unmarked() * draw
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        Some(ScoringRule::Standard)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MarkedSum;
+
+impl Scoring for MarkedSum {
+    fn score(&self, player: &Player, draw: u64, _turn: usize) -> u64 {
+        player.sum_of_marked() * draw
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        Some(ScoringRule::MarkedSum)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnmarkedCount;
+
+impl Scoring for UnmarkedCount {
+    fn score(&self, player: &Player, draw: u64, _turn: usize) -> u64 {
+        player.count_of_unmarked() * draw
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        Some(ScoringRule::UnmarkedCount)
+    }
+}
+
+// Every completed row, column and diagonal is worth its weight in points
+#[derive(Clone, Copy, Debug)]
+pub struct PatternWeighted {
+    pub row: u64,
+    pub col: u64,
+    pub diagonal: u64,
+}
+
+impl Default for PatternWeighted {
+    fn default() -> Self {
+        PatternWeighted { row: 1, col: 1, diagonal: 2 }
+    }
+}
+
+impl Scoring for PatternWeighted {
+    fn score(&self, player: &Player, _draw: u64, _turn: usize) -> u64 {
+        player.completed_rows() as u64 * self.row
+            + player.completed_cols() as u64 * self.col
+            + player.completed_diagonals() as u64 * self.diagonal
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        Some(ScoringRule::PatternWeighted { row: self.row, col: self.col, diagonal: self.diagonal })
+    }
+}
+
+// Adds a bonus for every turn left until `deadline` on top of another rule
+#[derive(Clone, Copy, Debug)]
+pub struct TurnBonus<S: Scoring> {
+    pub base: S,
+    pub bonus_per_turn: u64,
+    pub deadline: usize,
+}
+
+impl<S: Scoring> Scoring for TurnBonus<S> {
+    fn score(&self, player: &Player, draw: u64, turn: usize) -> u64 {
+        let turns_left = self.deadline.saturating_sub(turn) as u64;
+        self.base.score(player, draw, turn) + turns_left * self.bonus_per_turn
+    }
+
+    fn rule(&self) -> Option<ScoringRule> {
+        let base = Box::new(self.base.rule()?);
+        Some(ScoringRule::TurnBonus { base, bonus_per_turn: self.bonus_per_turn, deadline: self.deadline })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn winning_player() -> Player {
+        let mut player = Player::new([[22, 13, 17, 11, 0],
+                                      [8,  2,  23, 4,  24],
+                                      [21, 9,  14, 16, 7],
+                                      [6,  10, 3,  18, 5],
+                                      [1,  12, 20, 15, 19]]);
+
+        for draw in [8, 2, 23, 4, 24, 22, 14, 18, 19] {
+            player.handle_new_draw(draw);
+        }
+
+        player
+    }
+
+    #[test]
+    fn standard_scoring() {
+        let player = winning_player();
+        assert_eq!(StandardScoring.score(&player, 19, 9), player.sum_of_unmarked() * 19);
+    }
+
+    #[test]
+    fn marked_sum() {
+        let player = winning_player();
+        assert_eq!(MarkedSum.score(&player, 19, 9), (8+2+23+4+24+22+14+18+19) * 19);
+    }
+
+    #[test]
+    fn unmarked_count() {
+        let player = winning_player();
+        assert_eq!(UnmarkedCount.score(&player, 19, 9), 16 * 19);
+    }
+
+    #[test]
+    fn unmarked_count_repeated_draws() {
+        let mut board = [[0; 5]; 5];
+        for (i, val) in board.iter_mut().flatten().enumerate() {
+            *val = i as u64 + 1;
+        }
+        let mut player = Player::new(board);
+
+        for draw in [1, 1, 1, 2, 3, 4, 5] {
+            player.handle_new_draw(draw);
+        }
+        assert_eq!(player.count_of_unmarked(), 20);
+        assert_eq!(UnmarkedCount.score(&player, 5, 7), 100);
+    }
+
+    #[test]
+    fn pattern_weighted() {
+        let player = winning_player();
+        assert_eq!(player.completed_rows(), 1);
+        assert_eq!(player.completed_cols(), 0);
+        assert_eq!(player.completed_diagonals(), 1);
+
+        let scoring = PatternWeighted { row: 10, col: 20, diagonal: 50 };
+        assert_eq!(scoring.score(&player, 19, 9), 60);
+    }
+
+    #[test]
+    fn turn_bonus() {
+        let player = winning_player();
+        let scoring = TurnBonus { base: StandardScoring, bonus_per_turn: 100, deadline: 12 };
+        assert_eq!(scoring.score(&player, 19, 9), player.sum_of_unmarked() * 19 + 300);
+        assert_eq!(scoring.score(&player, 19, 20), player.sum_of_unmarked() * 19);
+    }
+}
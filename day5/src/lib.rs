@@ -1,55 +1,232 @@
 pub mod line;
+pub mod merge;
+pub mod query;
+pub mod render;
+pub mod sweep;
 
-//use std::fmt::{Display, Formatter};
+use std::fmt::{Display, Formatter};
 
 use line::*;
+use line::raster::Rasterization;
+use std::collections::HashMap;
+
+// Dense grids above this many cells are stored sparsely when the backend is picked automatically
+const DENSE_CELL_LIMIT: usize = 1 << 22;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Auto,
+    Dense,
+    Sparse,
+}
+
+#[derive(Default)]
+pub enum LineFilter {
+    #[default]
+    All,
+    AxisAligned,
+    Diagonal,
+    Custom(Box<dyn Fn(&Line) -> bool>),
+}
+
+impl LineFilter {
+    pub fn matches(&self, line: &Line) -> bool {
+        match self {
+            LineFilter::All => true,
+            LineFilter::AxisAligned => line.is_axis_aligned(),
+            LineFilter::Diagonal => line.is_diagonal(),
+            LineFilter::Custom(predicate) => predicate(line),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MapOptions {
+    pub backend: Backend,
+    pub filter: LineFilter,
+    pub rasterization: Rasterization,
+}
 
-pub struct ThermalMap(Vec<Vec<u64>>);
+enum Grid {
+    // Cell (x, y) lives at rows[y - origin.1][x - origin.0]. The origin is never below or
+    // right of (0, 0), so the grid always covers the origin
+    Dense { origin: (i64, i64), rows: Vec<Vec<u64>> },
+    Sparse(HashMap<(i64, i64), u64>),
+}
+
+pub struct ThermalMap(Grid);
 
 impl ThermalMap {
     pub fn new(lines: Vec<Line>) -> Self {
-        let furthest_x: usize = lines.iter().fold(0, |max, line| if line.furthest_x() > max { line.furthest_x() } else { max });
-        let furthest_y: usize = lines.iter().fold(0, |max, line| if line.furthest_y() > max { line.furthest_y() } else { max });
+        ThermalMap::with_backend(lines, Backend::Auto)
+    }
+
+    pub fn with_backend(lines: Vec<Line>, backend: Backend) -> Self {
+        ThermalMap::with_options(lines, MapOptions { backend, ..Default::default() })
+    }
+
+    pub fn with_filter(lines: Vec<Line>, filter: LineFilter) -> Self {
+        ThermalMap::with_options(lines, MapOptions { filter, ..Default::default() })
+    }
+
+    pub fn with_options(lines: Vec<Line>, options: MapOptions) -> Self {
+        let lines: Vec<Line> = lines.into_iter().filter(|line| options.filter.matches(line)).collect();
+
+        let nearest_x: i64 = lines.iter().fold(0, |min, line| if line.nearest_x() < min { line.nearest_x() } else { min });
+        let nearest_y: i64 = lines.iter().fold(0, |min, line| if line.nearest_y() < min { line.nearest_y() } else { min });
+        let furthest_x: i64 = lines.iter().fold(0, |max, line| if line.furthest_x() > max { line.furthest_x() } else { max });
+        let furthest_y: i64 = lines.iter().fold(0, |max, line| if line.furthest_y() > max { line.furthest_y() } else { max });
+
+        let dense = match options.backend {
+            Backend::Dense => true,
+            Backend::Sparse => false,
+            Backend::Auto => {
+                let width = furthest_x.checked_sub(nearest_x).and_then(|w| w.checked_add(1));
+                let height = furthest_y.checked_sub(nearest_y).and_then(|h| h.checked_add(1));
+                width.zip(height)
+                    .and_then(|(width, height)| width.checked_mul(height))
+                    .is_some_and(|cells| cells <= DENSE_CELL_LIMIT as i64)
+            },
+        };
+
+        if dense {
+            ThermalMap::new_dense(&lines, options.rasterization, (nearest_x, nearest_y), (furthest_x, furthest_y))
+        } else {
+            ThermalMap::new_sparse(&lines, options.rasterization)
+        }
+    }
+
+    fn new_dense(lines: &[Line], rasterization: Rasterization, origin: (i64, i64), furthest: (i64, i64)) -> Self {
+        let width = (furthest.0 - origin.0 + 1) as usize;
+        let height = (furthest.1 - origin.1 + 1) as usize;
 
         let mut column_vec: Vec<Vec<u64>> = Vec::new();
-        column_vec.reserve_exact(furthest_y+1);
-        for _ in 0..(furthest_y+1) {
-            column_vec.push(vec![0; furthest_x+1]);
+        column_vec.reserve_exact(height);
+        for _ in 0..height {
+            column_vec.push(vec![0; width]);
         }
 
-        for line in &lines {
-            for (x, y) in line.points() {
-                column_vec[y][x] += 1;
+        for line in lines {
+            for (x, y) in line.rasterize(rasterization) {
+                column_vec[(y - origin.1) as usize][(x - origin.0) as usize] += 1;
             }
         }
 
-        ThermalMap(column_vec)
+        ThermalMap(Grid::Dense { origin, rows: column_vec })
+    }
+
+    fn new_sparse(lines: &[Line], rasterization: Rasterization) -> Self {
+        let mut cells: HashMap<(i64, i64), u64> = HashMap::new();
+
+        for line in lines {
+            for point in line.rasterize(rasterization) {
+                *cells.entry(point).or_insert(0) += 1;
+            }
+        }
+
+        ThermalMap(Grid::Sparse(cells))
+    }
+
+    // Nearest and furthest corners of the map. Includes (0, 0) unless that would make
+    // a sparse map larger than a dense one may be
+    fn extent(&self) -> Option<((i64, i64), (i64, i64))> {
+        match &self.0 {
+            Grid::Dense { origin, rows } => rows.first().map(|row| {
+                (*origin, (origin.0 + row.len() as i64 - 1, origin.1 + rows.len() as i64 - 1))
+            }),
+            Grid::Sparse(cells) => {
+                let min = (cells.keys().map(|(x, _)| *x).min()?, cells.keys().map(|(_, y)| *y).min()?);
+                let max = (cells.keys().map(|(x, _)| *x).max()?, cells.keys().map(|(_, y)| *y).max()?);
+                let with_origin = ((min.0.min(0), min.1.min(0)), (max.0.max(0), max.1.max(0)));
+                match area_of(with_origin) <= DENSE_CELL_LIMIT as u128 {
+                    true => Some(with_origin),
+                    false => Some((min, max)),
+                }
+            },
+        }
+    }
+
+    // Every cell crossed by at least one line
+    fn cells(&self) -> Box<dyn Iterator<Item = ((i64, i64), u64)> + '_> {
+        match &self.0 {
+            Grid::Dense { origin, rows } => Box::new(rows.iter().enumerate().flat_map(move |(y, row)| {
+                row.iter().enumerate()
+                    .filter(|(_, count)| **count > 0)
+                    .map(move |(x, count)| ((x as i64 + origin.0, y as i64 + origin.1), *count))
+            })),
+            Grid::Sparse(cells) => Box::new(cells.iter().filter(|(_, count)| **count > 0).map(|(point, count)| (*point, *count))),
+        }
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.0, Grid::Sparse(_))
     }
 
-    fn at(&self, x: usize, y: usize) -> u64 {
-        self.0[y][x]
+    fn at(&self, x: i64, y: i64) -> u64 {
+        match &self.0 {
+            Grid::Dense { origin, rows } => {
+                let (Ok(col), Ok(row)) = (usize::try_from(x - origin.0), usize::try_from(y - origin.1)) else {
+                    return 0;
+                };
+                rows.get(row).and_then(|r| r.get(col)).copied().unwrap_or(0)
+            },
+            Grid::Sparse(cells) => cells.get(&(x, y)).copied().unwrap_or(0),
+        }
     }
 
-    pub fn overlaps(&self) -> Vec<(usize, usize)> {
+    pub fn overlaps(&self) -> Vec<(i64, i64)> {
         let mut retvec = vec![];
 
-        for (y, col) in self.0.iter().enumerate() {
-            for (x, _) in col.iter().enumerate() {
-                if self.at(x, y) > 1 {
-                    retvec.push((x, y));
+        match &self.0 {
+            Grid::Dense { origin, rows } => {
+                for (y, col) in rows.iter().enumerate() {
+                    for (x, count) in col.iter().enumerate() {
+                        if *count > 1 {
+                            retvec.push((x as i64 + origin.0, y as i64 + origin.1));
+                        }
+                    }
                 }
-            }
+            },
+            Grid::Sparse(cells) => {
+                retvec.extend(cells.iter().filter(|(_, count)| **count > 1).map(|(point, _)| *point));
+                // Same row-major order as the dense grid
+                retvec.sort_by_key(|&(x, y)| (y, x));
+            },
         }
 
         retvec
     }
 }
 
-//impl Display for ThermalMap {
-//    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-//        todo!()
-//    }
-//}
+// Number of cells between two corners, both inclusive
+fn area_of((min, max): ((i64, i64), (i64, i64))) -> u128 {
+    (max.0.abs_diff(min.0) as u128 + 1) * (max.1.abs_diff(min.1) as u128 + 1)
+}
+
+impl Display for ThermalMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.extent() else {
+            return Ok(());
+        };
+        if area_of(((min_x, min_y), (max_x, max_y))) > DENSE_CELL_LIMIT as u128 {
+            return writeln!(f, "Map spans {}x{} cells, too large to draw", max_x.abs_diff(min_x) + 1, max_y.abs_diff(min_y) + 1);
+        }
+
+        for y in min_y..=max_y {
+            let row: String = (min_x..=max_x)
+                .map(|x| match self.at(x, y) {
+                    0 => '.',
+                    count @ 1..=9 => char::from_digit(count as u32, 10).unwrap(),
+                    _ => '#',
+                })
+                .collect();
+            writeln!(f, "{row}")?;
+        }
+
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -59,12 +236,79 @@ mod tests {
     fn thermal_map_dimensions() {
         let line = line::Line::new((99,100), (100,100));
         let map = ThermalMap::new(vec![line]);
-        assert_eq!(map.0.len(), 101);
-        for row_vec in map.0.iter() {
-            assert_eq!(row_vec.len(), 101);
+        assert!(!map.is_sparse());
+        if let Grid::Dense { rows, .. } = &map.0 {
+            assert_eq!(rows.len(), 101);
+            for row_vec in rows.iter() {
+                assert_eq!(row_vec.len(), 101);
+            }
         }
     }
 
+    #[test]
+    fn thermal_map_huge_coordinates_sparse() {
+        let line0 = line::Line::new((1_000_000, 1_000_000), (1_000_000, 1_000_005));
+        let line1 = line::Line::new((999_998, 1_000_002), (1_000_003, 1_000_002));
+        let map = ThermalMap::new(vec![line0, line1]);
+
+        assert!(map.is_sparse());
+        assert_eq!(map.at(1_000_000, 1_000_002), 2);
+        assert_eq!(map.at(1_000_000, 1_000_006), 0);
+        assert_eq!(map.overlaps(), vec![(1_000_000, 1_000_002)]);
+        assert_eq!(map.to_string(), "\
+..1...
+..1...
+112111
+..1...
+..1...
+..1...
+");
+
+        let far_apart = ThermalMap::new(vec![line::Line::new((0,0), (1,0)), line::Line::new((3_000_000, 3_000_000), (3_000_000, 3_000_001))]);
+        assert!(far_apart.is_sparse());
+        assert_eq!(far_apart.to_string(), "Map spans 3000001x3000002 cells, too large to draw\n");
+    }
+
+    #[test]
+    fn thermal_map_negative_coordinates() {
+        let line0 = line::Line::new((-3,-1), (2,-1));
+        let line1 = line::Line::new((0,0), (-2,-2));
+        let map = ThermalMap::new(vec![line0, line1]);
+
+        assert!(!map.is_sparse());
+        assert_eq!(map.at(-1,-1), 2);
+        assert_eq!(map.at(-3,-1), 1);
+        assert_eq!(map.at(-4,-1), 0);
+        assert_eq!(map.overlaps(), vec![(-1,-1)]);
+        assert_eq!(map.to_string(), "\
+.1....
+112111
+...1..
+");
+    }
+
+    #[test]
+    fn thermal_map_backends_agree() {
+        let lines = || vec![line::Line::new((1,10), (6,10)),
+                            line::Line::new((9,1), (1,9)),
+                            line::Line::new((10,5), (4,5)),
+                            line::Line::new((3,3), (3,2)),
+                            line::Line::new((8,1), (8,5)),
+                            line::Line::new((7,5), (3,1)),
+                            line::Line::new((1,10), (3,10)),
+                            line::Line::new((4,5), (2,5)),
+                            line::Line::new((1,1), (9,9)),
+                            line::Line::new((6,6), (9,3))];
+
+        let dense = ThermalMap::with_backend(lines(), Backend::Dense);
+        let sparse = ThermalMap::with_backend(lines(), Backend::Sparse);
+
+        assert!(sparse.is_sparse());
+        assert_eq!(dense.overlaps().len(), 12);
+        assert_eq!(dense.overlaps(), sparse.overlaps());
+        assert_eq!(dense.to_string(), sparse.to_string());
+    }
+
     #[test]
     fn thermal_map_single_nondiagonal_line() {
         let line = line::Line::new((4,4), (6,4));
@@ -132,11 +376,58 @@ mod tests {
         assert_eq!(map.at(1,2), 1);
     }
 
-    //#[test]
-    //fn thermal_map_display() {
-    //    let line = Line{start: (99,100), end: (100,100)};
-    //    let map = ThermalMap::new(vec![line]);
-    //}
+    #[test]
+    fn thermal_map_line_filter() {
+        let lines = || vec![line::Line::new((0,1), (2,1)),
+                            line::Line::new((1,0), (1,2)),
+                            line::Line::new((0,0), (2,2)),
+                            line::Line::new((2,0), (0,2))];
+
+        assert_eq!(ThermalMap::with_filter(lines(), LineFilter::All).at(1,1), 4);
+        assert_eq!(ThermalMap::with_filter(lines(), LineFilter::AxisAligned).at(1,1), 2);
+        assert_eq!(ThermalMap::with_filter(lines(), LineFilter::Diagonal).at(1,1), 2);
+        assert_eq!(ThermalMap::with_filter(lines(), LineFilter::Custom(Box::new(|line| line.is_vertical()))).at(1,1), 1);
+    }
+
+    #[test]
+    fn thermal_map_arbitrary_angles() {
+        let line0 = line::Line::new((0,0), (6,4));
+        let line1 = line::Line::new((3,0), (3,4));
+
+        let map = ThermalMap::new(vec![line0.clone(), line1.clone()]);
+        assert!(map.overlaps().is_empty());
+
+        let options = MapOptions { rasterization: Rasterization::Lattice, ..Default::default() };
+        let map = ThermalMap::with_options(vec![line0.clone(), line1.clone()], options);
+        assert_eq!(map.overlaps(), vec![(3,2)]);
+
+        let options = MapOptions { rasterization: Rasterization::Bresenham, ..Default::default() };
+        let map = ThermalMap::with_options(vec![line0, line1], options);
+        assert_eq!(map.overlaps(), vec![(3,2)]);
+    }
+
+    #[test]
+    fn thermal_map_display() {
+        let lines: Vec<Line> = ["0,9 -> 5,9", "8,0 -> 0,8", "9,4 -> 3,4", "2,2 -> 2,1", "7,0 -> 7,4",
+                                "6,4 -> 2,0", "0,9 -> 2,9", "3,4 -> 1,4", "0,0 -> 8,8", "5,5 -> 8,2"]
+            .iter()
+            .map(|s| std::str::FromStr::from_str(s).unwrap())
+            .collect();
+        let map = ThermalMap::with_filter(lines, LineFilter::AxisAligned);
+
+        assert_eq!(map.to_string(), "\
+.......1..
+..1....1..
+..1....1..
+.......1..
+.112111211
+..........
+..........
+..........
+..........
+222111....
+");
+    }
 
     #[test]
     fn thermal_map_overlaps() {
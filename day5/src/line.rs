@@ -1,3 +1,5 @@
+pub mod raster;
+
 use thiserror::Error;
 use std::str::FromStr;
 
@@ -9,29 +11,37 @@ pub enum LineError {
     ParseError,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Line {
-    start: (usize, usize),
-    end: (usize, usize),
+    start: (i64, i64),
+    end: (i64, i64),
 }
 
 impl Line {
-    pub fn new(start: (usize, usize), end: (usize, usize)) -> Self {
+    pub fn new(start: (i64, i64), end: (i64, i64)) -> Self {
         Line{start, end}
     }
 
-    fn is_horizontal(&self) -> bool {
+    pub fn is_horizontal(&self) -> bool {
         self.start.1 == self.end.1
     }
 
-    fn is_vertical(&self) -> bool {
+    pub fn is_vertical(&self) -> bool {
         self.start.0 == self.end.0
     }
 
-    fn is_45_degrees(&self) -> bool {
+    pub fn is_45_degrees(&self) -> bool {
         self.start.0.abs_diff(self.end.0) == self.start.1.abs_diff(self.end.1)
     }
 
+    pub fn is_axis_aligned(&self) -> bool {
+        self.is_horizontal() || self.is_vertical()
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.is_45_degrees() && !self.is_axis_aligned()
+    }
+
     fn direction(&self) -> LineDirection {
         if self.is_horizontal() {
             if self.start.0 < self.end.0 {
@@ -58,11 +68,37 @@ impl Line {
         }
     }
 
-    pub fn furthest_x(&self) -> usize {
+    pub fn start(&self) -> (i64, i64) {
+        self.start
+    }
+
+    pub fn end(&self) -> (i64, i64) {
+        self.end
+    }
+
+    pub fn family(&self) -> Option<Family> {
+        match self.direction() {
+            LineDirection::Left | LineDirection::Right => Some(Family::Horizontal),
+            LineDirection::Up | LineDirection::Down => Some(Family::Vertical),
+            LineDirection::DownRight | LineDirection::UpLeft => Some(Family::Diagonal),
+            LineDirection::DownLeft | LineDirection::UpRight => Some(Family::AntiDiagonal),
+            LineDirection::Other => None,
+        }
+    }
+
+    pub fn nearest_x(&self) -> i64 {
+        self.start.0.min(self.end.0)
+    }
+
+    pub fn nearest_y(&self) -> i64 {
+        self.start.1.min(self.end.1)
+    }
+
+    pub fn furthest_x(&self) -> i64 {
         self.start.0.max(self.end.0)
     }
 
-    pub fn furthest_y(&self) -> usize {
+    pub fn furthest_y(&self) -> i64 {
         self.start.1.max(self.end.1)
     }
 
@@ -89,17 +125,79 @@ impl FromStr for Line {
         let mut sp = s.split(" -> ");
 
         let mut first_point_sp = sp.next().ok_or(LineError::ParseError)?.split(',');
-        let x0 = first_point_sp.next().ok_or(LineError::ParseError)?.parse::<usize>().or(Err(LineError::ParseError))?;
-        let y0 = first_point_sp.next().ok_or(LineError::ParseError)?.parse::<usize>().or(Err(LineError::ParseError))?;
+        let x0 = first_point_sp.next().ok_or(LineError::ParseError)?.parse::<i64>().or(Err(LineError::ParseError))?;
+        let y0 = first_point_sp.next().ok_or(LineError::ParseError)?.parse::<i64>().or(Err(LineError::ParseError))?;
 
         let mut second_point_sp = sp.next().ok_or(LineError::ParseError)?.split(',');
-        let x1 = second_point_sp.next().ok_or(LineError::ParseError)?.parse::<usize>().or(Err(LineError::ParseError))?;
-        let y1 = second_point_sp.next().ok_or(LineError::ParseError)?.parse::<usize>().or(Err(LineError::ParseError))?;
+        let x1 = second_point_sp.next().ok_or(LineError::ParseError)?.parse::<i64>().or(Err(LineError::ParseError))?;
+        let y1 = second_point_sp.next().ok_or(LineError::ParseError)?.parse::<i64>().or(Err(LineError::ParseError))?;
 
         Ok(Line{start: (x0,y0), end: (x1,y1)})
     }
 }
 
+// Groups of parallel lines that pass through every lattice point between their ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl Family {
+    pub const ALL: [Family; 4] = [Family::Horizontal, Family::Vertical, Family::Diagonal, Family::AntiDiagonal];
+
+    // (a, b) such that a*x + b*y is the same for every point of a line in this family
+    pub fn coefficients(&self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (-1, 1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    pub fn key(&self, point: (i64, i64)) -> i64 {
+        let (a, b) = self.coefficients();
+        a * point.0 + b * point.1
+    }
+
+    pub fn position(&self, point: (i64, i64)) -> i64 {
+        match self {
+            Family::Vertical => point.1,
+            _ => point.0,
+        }
+    }
+
+    pub fn point(&self, key: i64, position: i64) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (position, key),
+            Family::Vertical => (key, position),
+            Family::Diagonal => (position, key + position),
+            Family::AntiDiagonal => (position, key - position),
+        }
+    }
+
+    // Lattice point where lines of two families with given keys cross
+    pub fn crossing(&self, key: i64, other: Family, other_key: i64) -> Option<(i64, i64)> {
+        let (a0, b0) = self.coefficients();
+        let (a1, b1) = other.coefficients();
+        let det = a0 * b1 - a1 * b0;
+        if det == 0 {
+            return None;
+        }
+
+        let x = key * b1 - other_key * b0;
+        let y = a0 * other_key - a1 * key;
+        if x % det != 0 || y % det != 0 {
+            return None;
+        }
+
+        Some((x / det, y / det))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum LineDirection {
     Up,
@@ -115,13 +213,13 @@ enum LineDirection {
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct LineIter {
-    current: (usize, usize),
-    points_left: usize,
+    current: (i64, i64),
+    points_left: u64,
     direction: LineDirection,
 }
 
 impl Iterator for LineIter {
-    type Item = (usize, usize);
+    type Item = (i64, i64);
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.points_left > 0 {
@@ -164,6 +262,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn line_from_str_negative() -> Result<(), Box<dyn std::error::Error>> {
+
+        let input = "-25,4 -> 21,-37";
+
+        let parsed_line: Line = FromStr::from_str(input)?;
+        assert_eq!(parsed_line, Line{start: (-25,4), end: (21,-37)});
+
+        assert!(Line::from_str("--25,4 -> 21,37").is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn line_horizontal() {
         assert!(Line{start: (15,15), end: (18,15)}.is_horizontal());
@@ -189,6 +300,19 @@ mod tests {
         assert!(Line{start: (5,0), end: (0,5)}.is_45_degrees());
     }
 
+    #[test]
+    fn line_axis_aligned_and_diagonal() {
+        assert!(Line{start: (15,15), end: (18,15)}.is_axis_aligned());
+        assert!(Line{start: (15,15), end: (15,18)}.is_axis_aligned());
+        assert!(Line{start: (15,15), end: (15,15)}.is_axis_aligned());
+        assert!(!Line{start: (15,15), end: (18,18)}.is_axis_aligned());
+
+        assert!(Line{start: (15,15), end: (18,18)}.is_diagonal());
+        assert!(Line{start: (5,0), end: (0,5)}.is_diagonal());
+        assert!(!Line{start: (15,15), end: (15,15)}.is_diagonal());
+        assert!(!Line{start: (10,10), end: (15,16)}.is_diagonal());
+    }
+
     #[test]
     fn line_direction() {
         assert_eq!(Line{start: (15,15), end: (18,15)}.direction(), LineDirection::Right);
@@ -204,6 +328,30 @@ mod tests {
         assert_eq!(Line{start: (10,10), end: (15,16)}.direction(), LineDirection::Other);
     }
 
+    #[test]
+    fn line_family() {
+        assert_eq!(Line{start: (18,15), end: (15,15)}.family(), Some(Family::Horizontal));
+        assert_eq!(Line{start: (15,15), end: (15,15)}.family(), Some(Family::Horizontal));
+        assert_eq!(Line{start: (15,10), end: (15,15)}.family(), Some(Family::Vertical));
+        assert_eq!(Line{start: (15,15), end: (10,10)}.family(), Some(Family::Diagonal));
+        assert_eq!(Line{start: (0,5), end: (5,0)}.family(), Some(Family::AntiDiagonal));
+        assert_eq!(Line{start: (10,10), end: (15,16)}.family(), None);
+    }
+
+    #[test]
+    fn family_crossing() {
+        assert_eq!(Family::Horizontal.crossing(4, Family::Vertical, 7), Some((7,4)));
+        assert_eq!(Family::Diagonal.crossing(0, Family::AntiDiagonal, 6), Some((3,3)));
+        assert_eq!(Family::Diagonal.crossing(0, Family::AntiDiagonal, 5), None);
+        assert_eq!(Family::Diagonal.crossing(1, Family::Diagonal, 1), None);
+
+        for family in Family::ALL {
+            let point = family.point(3, 8);
+            assert_eq!(family.key(point), 3);
+            assert_eq!(family.position(point), 8);
+        }
+    }
+
     #[test]
     fn line_create_iter() {
         assert_eq!(
@@ -256,6 +404,17 @@ mod tests {
         assert_eq!(up_iter.next(), Some((5,3)));
         assert_eq!(up_iter.next(), None);
 
+        let mut zero_iter = LineIter{current:(1,1), points_left: 2, direction: LineDirection::UpLeft};
+        assert_eq!(zero_iter.next(), Some((1,1)));
+        assert_eq!(zero_iter.next(), Some((0,0)));
+        assert_eq!(zero_iter.next(), None);
+
+        let mut negative_iter = LineIter{current:(-1,2), points_left: 3, direction: LineDirection::Left};
+        assert_eq!(negative_iter.next(), Some((-1,2)));
+        assert_eq!(negative_iter.next(), Some((-2,2)));
+        assert_eq!(negative_iter.next(), Some((-3,2)));
+        assert_eq!(negative_iter.next(), None);
+
         let mut diagonal_iter = LineIter{current:(1,1), points_left: 4, direction: LineDirection::DownRight};
         assert_eq!(diagonal_iter.next(), Some((1,1)));
         assert_eq!(diagonal_iter.next(), Some((2,2)));
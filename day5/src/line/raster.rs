@@ -0,0 +1,188 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Rasterization {
+    // Horizontal, vertical and 45 degree lines only, others yield no points
+    #[default]
+    Orthogonal,
+    // Closest grid cell for every step along the major axis
+    Bresenham,
+    // Only points where the line crosses the grid exactly
+    Lattice,
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl Line {
+    pub fn rasterize(&self, mode: Rasterization) -> RasterIter {
+        match mode {
+            Rasterization::Orthogonal if self.direction() == LineDirection::Other => {
+                RasterIter::Lattice(LatticeIter{ current: self.start, step: (0, 0), points_left: 0 })
+            },
+            Rasterization::Orthogonal | Rasterization::Lattice => RasterIter::Lattice(LatticeIter::new(self)),
+            Rasterization::Bresenham => RasterIter::Bresenham(BresenhamIter::new(self)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RasterIter {
+    Lattice(LatticeIter),
+    Bresenham(BresenhamIter),
+}
+
+impl Iterator for RasterIter {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            RasterIter::Lattice(iter) => iter.next(),
+            RasterIter::Bresenham(iter) => iter.next(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LatticeIter {
+    current: (i64, i64),
+    step: (i64, i64),
+    points_left: u64,
+}
+
+impl LatticeIter {
+    fn new(line: &Line) -> Self {
+        let (dx, dy) = (line.end.0 - line.start.0, line.end.1 - line.start.1);
+        let steps = gcd(dx.unsigned_abs(), dy.unsigned_abs());
+
+        if steps == 0 {
+            return LatticeIter{ current: line.start, step: (0, 0), points_left: 1 };
+        }
+
+        LatticeIter{
+            current: line.start,
+            step: (dx / steps as i64, dy / steps as i64),
+            points_left: steps + 1,
+        }
+    }
+}
+
+impl Iterator for LatticeIter {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.points_left == 0 {
+            return None;
+        }
+
+        let to_return = self.current;
+        self.points_left -= 1;
+        if self.points_left > 0 {
+            self.current = (self.current.0 + self.step.0, self.current.1 + self.step.1);
+        }
+
+        Some(to_return)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BresenhamIter {
+    current: (i64, i64),
+    sign: (i64, i64),
+    dx: i64,
+    dy: i64,
+    err: i64,
+    points_left: u64,
+}
+
+impl BresenhamIter {
+    fn new(line: &Line) -> Self {
+        let (dx, dy) = (line.end.0 - line.start.0, line.end.1 - line.start.1);
+
+        BresenhamIter{
+            current: line.start,
+            sign: (dx.signum(), dy.signum()),
+            dx: dx.abs(),
+            dy: -dy.abs(),
+            err: dx.abs() - dy.abs(),
+            points_left: dx.unsigned_abs().max(dy.unsigned_abs()) + 1,
+        }
+    }
+}
+
+impl Iterator for BresenhamIter {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.points_left == 0 {
+            return None;
+        }
+
+        let to_return = self.current;
+        self.points_left -= 1;
+        if self.points_left > 0 {
+            let e2 = 2 * self.err;
+            if e2 >= self.dy {
+                self.err += self.dy;
+                self.current.0 += self.sign.0;
+            }
+            if e2 <= self.dx {
+                self.err += self.dx;
+                self.current.1 += self.sign.1;
+            }
+        }
+
+        Some(to_return)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterize_orthogonal_skips_other() {
+        let line = Line::new((0,0), (6,4));
+        assert_eq!(line.rasterize(Rasterization::Orthogonal).count(), 0);
+    }
+
+    #[test]
+    fn rasterize_lattice() {
+        let line = Line::new((0,0), (6,4));
+        assert_eq!(line.rasterize(Rasterization::Lattice).collect::<Vec<_>>(), vec![(0,0), (3,2), (6,4)]);
+
+        let line = Line::new((7,1), (0,3));
+        assert_eq!(line.rasterize(Rasterization::Lattice).collect::<Vec<_>>(), vec![(7,1), (0,3)]);
+
+        let line = Line::new((2,2), (2,2));
+        assert_eq!(line.rasterize(Rasterization::Lattice).collect::<Vec<_>>(), vec![(2,2)]);
+
+        let line = Line::new((-3,-2), (3,2));
+        assert_eq!(line.rasterize(Rasterization::Lattice).collect::<Vec<_>>(), vec![(-3,-2), (0,0), (3,2)]);
+    }
+
+    #[test]
+    fn rasterize_bresenham() {
+        let line = Line::new((0,0), (4,2));
+        assert_eq!(line.rasterize(Rasterization::Bresenham).collect::<Vec<_>>(), vec![(0,0), (1,1), (2,1), (3,2), (4,2)]);
+
+        let line = Line::new((1,5), (0,0));
+        assert_eq!(line.rasterize(Rasterization::Bresenham).collect::<Vec<_>>(), vec![(1,5), (1,4), (1,3), (0,2), (0,1), (0,0)]);
+    }
+
+    #[test]
+    fn rasterize_modes_agree_on_supported_lines() {
+        let lines = [Line::new((15,15), (18,15)),
+                     Line::new((15,15), (15,10)),
+                     Line::new((6,1), (1,6)),
+                     Line::new((1,1), (4,4))];
+
+        for line in lines {
+            let expected: Vec<_> = line.points().collect();
+            assert_eq!(line.rasterize(Rasterization::Orthogonal).collect::<Vec<_>>(), expected);
+            assert_eq!(line.rasterize(Rasterization::Lattice).collect::<Vec<_>>(), expected);
+            assert_eq!(line.rasterize(Rasterization::Bresenham).collect::<Vec<_>>(), expected);
+        }
+    }
+}
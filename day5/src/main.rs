@@ -1,5 +1,5 @@
 use day5::line::*;
-use day5::ThermalMap;
+use day5::{LineFilter, ThermalMap};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -7,9 +7,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let file = File::open("inputfile").expect("Failed to find file with input data");
     let reader = BufReader::new(file);
-    let lines: Vec<Line> = reader.lines().flatten().map(|s| std::str::FromStr::from_str(s.as_ref()).unwrap()).collect();
+    let lines: Vec<Line> = reader.lines().map_while(Result::ok).map(|s| std::str::FromStr::from_str(s.as_ref()).unwrap()).collect();
 
-    let map = ThermalMap::new(lines);
+    let map = ThermalMap::with_filter(lines.clone(), LineFilter::AxisAligned);
+    println!("Number of overlapping points without diagonals: {}", map.overlaps().len());
+
+    let map = ThermalMap::with_filter(lines, LineFilter::All);
     println!("Number of overlapping points: {}", map.overlaps().len());
 
     Ok(())
@@ -0,0 +1,135 @@
+use crate::line::{Family, Line};
+use std::collections::BTreeMap;
+
+// Collinear lines keyed by family and the key shared by their points, as (position range, input index)
+type CollinearGroups = BTreeMap<(Family, i64), Vec<(i64, i64, usize)>>;
+
+fn collinear_groups(lines: &[Line]) -> CollinearGroups {
+    let mut groups: CollinearGroups = BTreeMap::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(family) = line.family() {
+            let (from, to) = (family.position(line.start()), family.position(line.end()));
+            groups.entry((family, family.key(line.start()))).or_default().push((from.min(to), from.max(to), idx));
+        }
+    }
+
+    for intervals in groups.values_mut() {
+        intervals.sort_unstable();
+    }
+
+    groups
+}
+
+// Replaces collinear lines that overlap or touch with a single maximal line.
+// Lines of other angles are kept unchanged after the merged ones.
+pub fn merge_collinear(lines: &[Line]) -> Vec<Line> {
+    let mut merged = vec![];
+
+    for ((family, key), intervals) in collinear_groups(lines) {
+        let mut current: Option<(i64, i64)> = None;
+
+        for (from, to, _) in intervals {
+            current = match current {
+                Some((cur_from, cur_to)) if from <= cur_to + 1 => Some((cur_from, cur_to.max(to))),
+                Some((cur_from, cur_to)) => {
+                    merged.push(Line::new(family.point(key, cur_from), family.point(key, cur_to)));
+                    Some((from, to))
+                },
+                None => Some((from, to)),
+            };
+        }
+
+        if let Some((cur_from, cur_to)) = current {
+            merged.push(Line::new(family.point(key, cur_from), family.point(key, cur_to)));
+        }
+    }
+
+    merged.extend(lines.iter().filter(|line| line.family().is_none()).cloned());
+    merged
+}
+
+// Number of shared points for every pair of collinear lines that overlap, as (first index, second index, length)
+pub fn collinear_overlap_lengths(lines: &[Line]) -> Vec<(usize, usize, u64)> {
+    let mut overlaps = vec![];
+
+    for intervals in collinear_groups(lines).values() {
+        let mut active: Vec<(i64, usize)> = vec![];
+
+        for &(from, to, idx) in intervals {
+            active.retain(|(active_to, _)| *active_to >= from);
+            for &(active_to, active_idx) in &active {
+                let length = active_to.min(to) - from + 1;
+                overlaps.push((active_idx.min(idx), active_idx.max(idx), length as u64));
+            }
+            active.push((to, idx));
+        }
+    }
+
+    overlaps.sort_unstable();
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn merge_overlapping_and_adjacent() {
+        let lines = vec![Line::new((0,0), (5,0)),
+                         Line::new((8,0), (3,0)),
+                         Line::new((9,0), (12,0)),
+                         Line::new((14,0), (15,0)),
+                         Line::new((2,5), (2,1)),
+                         Line::new((2,7), (2,9))];
+
+        assert_eq!(merge_collinear(&lines), vec![Line::new((0,0), (12,0)),
+                                                 Line::new((14,0), (15,0)),
+                                                 Line::new((2,1), (2,5)),
+                                                 Line::new((2,7), (2,9))]);
+    }
+
+    #[test]
+    fn merge_diagonals() {
+        let lines = vec![Line::new((0,0), (3,3)),
+                         Line::new((4,4), (6,6)),
+                         Line::new((1,2), (3,4)),
+                         Line::new((6,0), (3,3)),
+                         Line::new((1,5), (2,4)),
+                         Line::new((0,0), (6,4))];
+
+        assert_eq!(merge_collinear(&lines), vec![Line::new((0,0), (6,6)),
+                                                 Line::new((1,2), (3,4)),
+                                                 Line::new((1,5), (6,0)),
+                                                 Line::new((0,0), (6,4))]);
+    }
+
+    #[test]
+    fn merge_covers_same_points() {
+        let lines = vec![Line::new((0,9), (5,9)), Line::new((8,0), (0,8)), Line::new((9,4), (3,4)),
+                         Line::new((2,2), (2,1)), Line::new((7,0), (7,4)), Line::new((6,4), (2,0)),
+                         Line::new((0,9), (2,9)), Line::new((3,4), (1,4)), Line::new((0,0), (8,8)),
+                         Line::new((5,5), (8,2)), Line::new((7,5), (7,1))];
+
+        let merged = merge_collinear(&lines);
+        assert_eq!(merged.len(), lines.len() - 3);
+
+        let covered = |lines: &[Line]| lines.iter().flat_map(Line::points).collect::<HashSet<_>>();
+        assert_eq!(covered(&merged), covered(&lines));
+        assert!(collinear_overlap_lengths(&merged).is_empty());
+    }
+
+    #[test]
+    fn overlap_lengths() {
+        let lines = vec![Line::new((0,0), (5,0)),
+                         Line::new((8,0), (3,0)),
+                         Line::new((6,0), (12,0)),
+                         Line::new((3,3), (0,0)),
+                         Line::new((2,2), (4,4)),
+                         Line::new((5,1), (5,5)),
+                         Line::new((4,5), (8,5))];
+
+        assert_eq!(collinear_overlap_lengths(&lines), vec![(0, 1, 3), (1, 2, 3), (3, 4, 2)]);
+    }
+}
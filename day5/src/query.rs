@@ -0,0 +1,206 @@
+use crate::render::BoundingBox;
+use crate::{Grid, ThermalMap};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+enum Index {
+    // Prefix sums with an extra leading row and column of zeros
+    Grid { origin: (i64, i64), sums: Vec<Vec<u64>> },
+    // Sorted x coordinates of dangerous cells in every row
+    Rows(BTreeMap<i64, Vec<i64>>),
+}
+
+// Counts cells at or above a danger threshold inside rectangles
+pub struct DangerIndex(Index);
+
+impl DangerIndex {
+    pub fn count(&self, area: &BoundingBox) -> u64 {
+        match &self.0 {
+            Index::Grid { origin, sums } => {
+                let height = sums.len() as i64 - 1;
+                let width = sums[0].len() as i64 - 1;
+                let clamp = |value: i64, max: i64| value.clamp(0, max) as usize;
+
+                let (r0, r1) = (clamp(area.min.1 - origin.1, height), clamp(area.max.1 - origin.1 + 1, height));
+                let (c0, c1) = (clamp(area.min.0 - origin.0, width), clamp(area.max.0 - origin.0 + 1, width));
+                if r0 >= r1 || c0 >= c1 {
+                    return 0;
+                }
+
+                sums[r1][c1] + sums[r0][c0] - sums[r0][c1] - sums[r1][c0]
+            },
+            Index::Rows(rows) => {
+                rows.range(area.min.1..=area.max.1)
+                    .map(|(_, xs)| (xs.partition_point(|x| *x <= area.max.0) - xs.partition_point(|x| *x < area.min.0)) as u64)
+                    .sum()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DangerZone {
+    // Row-major order
+    pub cells: Vec<(i64, i64)>,
+    pub peak: u64,
+}
+
+impl DangerZone {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+impl ThermalMap {
+    pub fn danger_at(&self, x: i64, y: i64) -> u64 {
+        self.at(x, y)
+    }
+
+    // Highest counts first, ties in row-major order
+    pub fn hottest(&self, k: usize) -> Vec<((i64, i64), u64)> {
+        let mut cells: Vec<((i64, i64), u64)> = self.cells().collect();
+        let key = |&((x, y), count): &((i64, i64), u64)| (Reverse(count), y, x);
+
+        if k < cells.len() {
+            cells.select_nth_unstable_by_key(k, key);
+            cells.truncate(k);
+        }
+        cells.sort_unstable_by_key(key);
+
+        cells
+    }
+
+    // Only cells crossed by a line are dangerous, even with a threshold of 0
+    pub fn danger_index(&self, threshold: u64) -> DangerIndex {
+        let threshold = threshold.max(1);
+        match &self.0 {
+            Grid::Dense { origin, rows } => {
+                let width = rows.first().map_or(0, |row| row.len());
+                let mut sums = vec![vec![0; width + 1]; rows.len() + 1];
+
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, count) in row.iter().enumerate() {
+                        let dangerous = u64::from(*count >= threshold);
+                        sums[y + 1][x + 1] = dangerous + sums[y][x + 1] + sums[y + 1][x] - sums[y][x];
+                    }
+                }
+
+                DangerIndex(Index::Grid { origin: *origin, sums })
+            },
+            Grid::Sparse(_) => {
+                let mut rows: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+                for ((x, y), _) in self.cells().filter(|(_, count)| *count >= threshold) {
+                    rows.entry(y).or_default().push(x);
+                }
+                rows.values_mut().for_each(|xs| xs.sort_unstable());
+
+                DangerIndex(Index::Rows(rows))
+            },
+        }
+    }
+
+    // Groups of touching cells at or above the threshold, largest first.
+    // Diagonal neighbours count as touching so 45 degree lines form a single zone.
+    pub fn danger_zones(&self, threshold: u64) -> Vec<DangerZone> {
+        let mut unvisited: HashSet<(i64, i64)> = self.cells()
+            .filter(|(_, count)| *count >= threshold.max(1))
+            .map(|(point, _)| point)
+            .collect();
+
+        let mut starts: Vec<(i64, i64)> = unvisited.iter().copied().collect();
+        starts.sort_unstable_by_key(|&(x, y)| (y, x));
+
+        let mut zones = vec![];
+        for start in starts {
+            if !unvisited.remove(&start) {
+                continue;
+            }
+
+            let mut cells = vec![];
+            let mut queue = VecDeque::from([start]);
+            while let Some((x, y)) = queue.pop_front() {
+                cells.push((x, y));
+                for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                    if unvisited.remove(&(x + dx, y + dy)) {
+                        queue.push_back((x + dx, y + dy));
+                    }
+                }
+            }
+
+            cells.sort_unstable_by_key(|&(x, y)| (y, x));
+            let peak = cells.iter().map(|(x, y)| self.at(*x, *y)).max().unwrap_or(0);
+            zones.push(DangerZone { cells, peak });
+        }
+
+        zones.sort_by_key(|zone| Reverse(zone.size()));
+        zones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line::Line;
+    use crate::Backend;
+    use std::str::FromStr;
+
+    fn example_map(backend: Backend) -> ThermalMap {
+        let lines = ["0,9 -> 5,9", "8,0 -> 0,8", "9,4 -> 3,4", "2,2 -> 2,1", "7,0 -> 7,4",
+                     "6,4 -> 2,0", "0,9 -> 2,9", "3,4 -> 1,4", "0,0 -> 8,8", "5,5 -> 8,2"]
+            .iter()
+            .map(|s| Line::from_str(s).unwrap())
+            .collect();
+        ThermalMap::with_backend(lines, backend)
+    }
+
+    #[test]
+    fn danger_at() {
+        let map = example_map(Backend::Dense);
+        assert_eq!(map.danger_at(4, 4), 3);
+        assert_eq!(map.danger_at(9, 9), 0);
+        assert_eq!(map.danger_at(-5, 100), 0);
+    }
+
+    #[test]
+    fn hottest() {
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let map = example_map(backend);
+            assert_eq!(map.hottest(3), vec![((4,4), 3), ((6,4), 3), ((7,1), 2)]);
+            assert_eq!(map.hottest(1000).len(), map.cells().count());
+        }
+    }
+
+    #[test]
+    fn danger_index_count() {
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let map = example_map(backend);
+            let index = map.danger_index(2);
+
+            assert_eq!(index.count(&BoundingBox::new((0,0), (9,9))), 12);
+            assert_eq!(index.count(&BoundingBox::new((-100,-100), (100,100))), 12);
+            assert_eq!(index.count(&BoundingBox::new((3,3), (7,4))), 6);
+            assert_eq!(index.count(&BoundingBox::new((0,9), (0,9))), 1);
+            assert_eq!(index.count(&BoundingBox::new((20,20), (30,30))), 0);
+
+            assert_eq!(map.danger_index(3).count(&BoundingBox::new((0,0), (9,9))), 2);
+
+            let crossed = map.danger_index(0);
+            assert_eq!(crossed.count(&BoundingBox::new((0,0), (9,9))), 39);
+            assert_eq!(crossed.count(&BoundingBox::new((0,0), (9,9))), map.danger_index(1).count(&BoundingBox::new((0,0), (9,9))));
+            assert_eq!(crossed.count(&BoundingBox::new((20,20), (30,30))), 0);
+        }
+    }
+
+    #[test]
+    fn danger_zones() {
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let map = example_map(backend);
+            let zones = map.danger_zones(2);
+
+            assert_eq!(zones.iter().map(DangerZone::size).collect::<Vec<_>>(), vec![7, 3, 1, 1]);
+            assert_eq!(zones[0].peak, 3);
+            assert_eq!(zones[1].cells, vec![(0,9), (1,9), (2,9)]);
+            assert_eq!(zones.iter().map(DangerZone::size).sum::<usize>(), map.overlaps().len());
+        }
+    }
+}
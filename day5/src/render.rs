@@ -0,0 +1,172 @@
+use crate::{ThermalMap, DENSE_CELL_LIMIT};
+use std::io::{self, Write};
+
+// Heatmap stops from a single line up to the most dangerous cell, empty cells stay black
+const COLOR_RAMP: [[u8; 3]; 5] = [
+    [0, 0, 160],
+    [0, 160, 255],
+    [0, 220, 0],
+    [255, 230, 0],
+    [255, 0, 0],
+];
+
+// Inclusive on both corners
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: (i64, i64),
+    pub max: (i64, i64),
+}
+
+impl BoundingBox {
+    pub fn new(min: (i64, i64), max: (i64, i64)) -> Self {
+        BoundingBox {
+            min: (min.0.min(max.0), min.1.min(max.1)),
+            max: (min.0.max(max.0), min.1.max(max.1)),
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.max.0.abs_diff(self.min.0) + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        self.max.1.abs_diff(self.min.1) + 1
+    }
+
+    fn points(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        (self.min.1..=self.max.1).flat_map(move |y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+fn ramp(count: u64, max: u64) -> [u8; 3] {
+    if count == 0 {
+        return [0, 0, 0];
+    }
+
+    let t = if max > 1 { (count - 1) as f64 / (max - 1) as f64 } else { 0.0 };
+    let scaled = t * (COLOR_RAMP.len() - 1) as f64;
+    let idx = (scaled.floor() as usize).min(COLOR_RAMP.len() - 2);
+    let frac = scaled - idx as f64;
+
+    let (from, to) = (COLOR_RAMP[idx], COLOR_RAMP[idx + 1]);
+    [0, 1, 2].map(|c| (from[c] as f64 + (to[c] as f64 - from[c] as f64) * frac).round() as u8)
+}
+
+impl ThermalMap {
+    // Smallest box containing every cell crossed by a line
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut cells = self.cells().map(|(point, _)| point);
+        let first = cells.next()?;
+        Some(cells.fold(BoundingBox::new(first, first), |bbox, (x, y)| {
+            BoundingBox::new((bbox.min.0.min(x), bbox.min.1.min(y)), (bbox.max.0.max(x), bbox.max.1.max(y)))
+        }))
+    }
+
+    // Without a crop the whole map is drawn, as long as it isn't too large
+    fn image_area(&self, crop: Option<BoundingBox>) -> io::Result<BoundingBox> {
+        if let Some(crop) = crop {
+            return Ok(crop);
+        }
+
+        let (min, max) = self.extent().unwrap_or(((0, 0), (0, 0)));
+        let area = BoundingBox::new(min, max);
+        if area.width() as u128 * area.height() as u128 > DENSE_CELL_LIMIT as u128 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Map is too large to draw whole, pass a crop"));
+        }
+        Ok(area)
+    }
+
+    fn max_in(&self, area: &BoundingBox) -> u64 {
+        area.points().map(|(x, y)| self.at(x, y)).max().unwrap_or(0)
+    }
+
+    // Binary greyscale image, brighter cells have more overlapping lines
+    pub fn write_pgm<W: Write>(&self, out: &mut W, crop: Option<BoundingBox>) -> io::Result<()> {
+        let area = self.image_area(crop)?;
+        let max = self.max_in(&area).max(1);
+
+        write!(out, "P5\n{} {}\n255\n", area.width(), area.height())?;
+        let pixels: Vec<u8> = area.points().map(|(x, y)| (self.at(x, y) * 255 / max) as u8).collect();
+        out.write_all(&pixels)
+    }
+
+    // Binary color image, colored from blue to red by overlap count
+    pub fn write_ppm<W: Write>(&self, out: &mut W, crop: Option<BoundingBox>) -> io::Result<()> {
+        let area = self.image_area(crop)?;
+        let max = self.max_in(&area);
+
+        write!(out, "P6\n{} {}\n255\n", area.width(), area.height())?;
+        let pixels: Vec<u8> = area.points().flat_map(|(x, y)| ramp(self.at(x, y), max)).collect();
+        out.write_all(&pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line::Line;
+
+    fn cross_map() -> ThermalMap {
+        ThermalMap::new(vec![Line::new((1,2), (3,2)), Line::new((2,1), (2,3))])
+    }
+
+    #[test]
+    fn bounding_box() {
+        assert_eq!(cross_map().bounding_box(), Some(BoundingBox::new((1,1), (3,3))));
+    }
+
+    #[test]
+    fn ramp_colors() {
+        assert_eq!(ramp(0, 5), [0, 0, 0]);
+        assert_eq!(ramp(1, 5), COLOR_RAMP[0]);
+        assert_eq!(ramp(5, 5), COLOR_RAMP[4]);
+        assert_eq!(ramp(1, 1), COLOR_RAMP[0]);
+    }
+
+    #[test]
+    fn write_pgm() -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = vec![];
+        cross_map().write_pgm(&mut out, None)?;
+
+        let header = b"P5\n4 4\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(&out[header.len()..], &[0, 0, 0, 0,
+                                           0, 0, 127, 0,
+                                           0, 127, 255, 127,
+                                           0, 0, 127, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_ppm_cropped() -> Result<(), Box<dyn std::error::Error>> {
+        let map = cross_map();
+        let mut out = vec![];
+        map.write_ppm(&mut out, map.bounding_box())?;
+
+        let header = b"P6\n3 3\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 3 * 3 * 3);
+        // Center of the cross is the hottest cell
+        assert_eq!(&out[header.len() + 4 * 3..header.len() + 5 * 3], &COLOR_RAMP[4]);
+        assert_eq!(&out[header.len()..header.len() + 3], &[0, 0, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_far_sparse_map() -> Result<(), Box<dyn std::error::Error>> {
+        let map = ThermalMap::new(vec![Line::new((1_000_000, 1_000_000), (1_000_002, 1_000_000))]);
+        let mut out = vec![];
+        map.write_pgm(&mut out, None)?;
+        assert_eq!(out, b"P5\n3 1\n255\n\xff\xff\xff");
+
+        let map = ThermalMap::new(vec![Line::new((0, 0), (1, 0)), Line::new((3_000_000, 3_000_000), (3_000_001, 3_000_000))]);
+        let mut out = vec![];
+        assert_eq!(map.write_ppm(&mut out, None).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        map.write_ppm(&mut out, Some(BoundingBox::new((0, 0), (1, 1))))?;
+        assert_eq!(&out[..11], b"P6\n2 2\n255\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::line::{Family, Line};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Overlaps between vent lines computed from their geometry instead of rasterizing every point.
+// Like ThermalMap with the default rasterization, only horizontal, vertical and 45 degree lines count.
+
+type Ranges = HashMap<i64, Vec<(i64, i64)>>;
+
+struct Segment {
+    key: i64,
+    start: (i64, i64),
+    end: (i64, i64),
+}
+
+impl Segment {
+    // Range of keys of another family covered by this segment
+    fn span(&self, family: Family) -> (i64, i64) {
+        let (from, to) = (family.key(self.start), family.key(self.end));
+        (from.min(to), from.max(to))
+    }
+}
+
+fn group_by_family(lines: &[Line]) -> BTreeMap<Family, Vec<Segment>> {
+    let mut groups: BTreeMap<Family, Vec<Segment>> = BTreeMap::new();
+
+    for line in lines {
+        if let Some(family) = line.family() {
+            let (start, end) = (line.start(), line.end());
+            groups.entry(family).or_default().push(Segment{ key: family.key(start), start, end });
+        }
+    }
+
+    groups
+}
+
+// Position ranges covered by at least two collinear segments, merged and sorted for each key
+fn collinear_overlaps(family: Family, segments: &[Segment]) -> Ranges {
+    let mut intervals: Vec<(i64, i64, i64)> = segments.iter()
+        .map(|segment| {
+            let (from, to) = (family.position(segment.start), family.position(segment.end));
+            (segment.key, from.min(to), from.max(to))
+        })
+        .collect();
+    intervals.sort_unstable();
+
+    let mut overlaps: Ranges = HashMap::new();
+    let mut reach: Option<(i64, i64)> = None;
+
+    for (key, from, to) in intervals {
+        match reach {
+            Some((reach_key, reach_to)) if reach_key == key && from <= reach_to => {
+                let overlap = (from, to.min(reach_to));
+                let ranges = overlaps.entry(key).or_default();
+                match ranges.last_mut() {
+                    Some(last) if overlap.0 <= last.1 + 1 => last.1 = last.1.max(overlap.1),
+                    _ => ranges.push(overlap),
+                }
+                reach = Some((key, reach_to.max(to)));
+            },
+            _ => reach = Some((key, to)),
+        }
+    }
+
+    overlaps
+}
+
+enum Event {
+    Insert(i64),
+    Query(i64, (i64, i64)),
+    Remove(i64),
+}
+
+impl Event {
+    // Segment ends are inclusive, so inserts go before queries and removals after them
+    fn order(&self) -> u8 {
+        match self {
+            Event::Insert(_) => 0,
+            Event::Query(_, _) => 1,
+            Event::Remove(_) => 2,
+        }
+    }
+}
+
+// Sweeps over keys of `other`, where segments of `family` become intervals and segments of `other` become points
+fn crossings(family: Family, segments: &[Segment], other: Family, other_segments: &[Segment]) -> Vec<(i64, i64)> {
+    let mut events: Vec<(i64, Event)> = Vec::with_capacity(2 * segments.len() + other_segments.len());
+
+    for segment in segments {
+        let (from, to) = segment.span(other);
+        events.push((from, Event::Insert(segment.key)));
+        events.push((to, Event::Remove(segment.key)));
+    }
+
+    for segment in other_segments {
+        events.push((segment.key, Event::Query(segment.key, segment.span(family))));
+    }
+
+    events.sort_by_key(|(at, event)| (*at, event.order()));
+
+    let mut active: BTreeMap<i64, usize> = BTreeMap::new();
+    let mut points = vec![];
+
+    for (_, event) in events {
+        match event {
+            Event::Insert(key) => *active.entry(key).or_insert(0) += 1,
+            Event::Remove(key) => {
+                if let Some(count) = active.get_mut(&key) {
+                    *count -= 1;
+                    if *count == 0 {
+                        active.remove(&key);
+                    }
+                }
+            },
+            Event::Query(other_key, (from, to)) => {
+                points.extend(active.range(from..=to).filter_map(|(key, _)| family.crossing(*key, other, other_key)));
+            },
+        }
+    }
+
+    points
+}
+
+fn all_crossings(groups: &BTreeMap<Family, Vec<Segment>>) -> HashSet<(i64, i64)> {
+    let mut points = HashSet::new();
+
+    for (i, (family, segments)) in groups.iter().enumerate() {
+        for (other, other_segments) in groups.iter().skip(i + 1) {
+            points.extend(crossings(*family, segments, *other, other_segments));
+        }
+    }
+
+    points
+}
+
+pub fn overlaps(lines: &[Line]) -> Vec<(i64, i64)> {
+    let groups = group_by_family(lines);
+    let mut points = all_crossings(&groups);
+
+    for (family, segments) in &groups {
+        for (key, ranges) in collinear_overlaps(*family, segments) {
+            for (from, to) in ranges {
+                points.extend((from..=to).map(|position| family.point(key, position)));
+            }
+        }
+    }
+
+    let mut retvec: Vec<(i64, i64)> = points.into_iter().collect();
+    retvec.sort_by_key(|&(x, y)| (y, x));
+    retvec
+}
+
+// Same as overlaps().len(), without listing every point of collinear overlaps
+pub fn overlap_count(lines: &[Line]) -> usize {
+    let groups = group_by_family(lines);
+    let regions: Vec<(Family, Ranges)> = groups.iter()
+        .map(|(family, segments)| (*family, collinear_overlaps(*family, segments)))
+        .collect();
+
+    let mut count: usize = regions.iter()
+        .flat_map(|(_, by_key)| by_key.values().flatten())
+        .map(|(from, to)| (to - from + 1) as usize)
+        .sum();
+
+    // A point in collinear overlaps of several families is also a crossing, so it is only corrected for here
+    for point in all_crossings(&groups) {
+        let covering = regions.iter()
+            .filter(|(family, by_key)| {
+                let position = family.position(point);
+                by_key.get(&family.key(point)).is_some_and(|ranges| {
+                    let idx = ranges.partition_point(|(_, to)| *to < position);
+                    ranges.get(idx).is_some_and(|(from, _)| *from <= position)
+                })
+            })
+            .count();
+
+        match covering {
+            0 => count += 1,
+            n => count -= n - 1,
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ThermalMap;
+    use std::str::FromStr;
+
+    fn example_lines() -> Vec<Line> {
+        ["0,9 -> 5,9", "8,0 -> 0,8", "9,4 -> 3,4", "2,2 -> 2,1", "7,0 -> 7,4",
+         "6,4 -> 2,0", "0,9 -> 2,9", "3,4 -> 1,4", "0,0 -> 8,8", "5,5 -> 8,2"]
+            .iter()
+            .map(|s| Line::from_str(s).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn sweep_example() {
+        let lines = example_lines();
+        assert_eq!(overlap_count(&lines), 12);
+        assert_eq!(overlaps(&lines), ThermalMap::new(lines.clone()).overlaps());
+        assert_eq!(overlaps(&lines), vec![(7,1), (2,2), (5,3), (7,3), (3,4), (4,4), (6,4), (7,4), (5,5), (0,9), (1,9), (2,9)]);
+    }
+
+    #[test]
+    fn sweep_collinear() {
+        let lines = vec![Line::new((0,0), (10,0)),
+                         Line::new((5,0), (15,0)),
+                         Line::new((8,0), (20,0)),
+                         Line::new((10,0), (10,5)),
+                         Line::new((10,2), (10,8)),
+                         Line::new((5,5), (15,5))];
+
+        // (5..=15, 0) and (10, 2..=5)
+        assert_eq!(overlap_count(&lines), 11 + 4);
+        assert_eq!(overlaps(&lines).len(), 15);
+    }
+
+    #[test]
+    fn sweep_matches_thermal_map() {
+        let mut seed: u64 = 12345;
+        let mut next = |max: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % max
+        };
+
+        for _ in 0..20 {
+            let lines: Vec<Line> = (0..40)
+                .map(|_| {
+                    let (x, y, len) = (next(80) as i64 - 40, next(80) as i64 - 40, next(20) as i64);
+                    match next(8) {
+                        0 => Line::new((x, y), (x + len, y)),
+                        1 => Line::new((x, y), (x, y + len)),
+                        2 => Line::new((x, y), (x + len, y + len)),
+                        3 => Line::new((x, y), (x + len, y - len)),
+                        4 => Line::new((x + len, y), (x, y)),
+                        5 => Line::new((x, y + len), (x, y)),
+                        6 => Line::new((x + len, y + len), (x, y)),
+                        _ => Line::new((x, y), (x + next(7) as i64 + 1, y + next(5) as i64 + 8)),
+                    }
+                })
+                .collect();
+
+            let expected = ThermalMap::new(lines.clone()).overlaps();
+            assert_eq!(overlaps(&lines), expected);
+            assert_eq!(overlap_count(&lines), expected.len());
+        }
+    }
+}
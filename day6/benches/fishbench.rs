@@ -1,9 +1,59 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use day6::bench_function;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day6::part1;
+use day6::part2::{self, BigFishes};
+use std::str::FromStr;
+
+const DAYS: [u64; 5] = [18, 80, 256, 1024, 4096];
+const SIZES: [usize; 3] = [5, 300, 10_000];
+const MODULUS: u64 = 1_000_000_007;
+
+// Compare runs against a previous one with `cargo bench -- --baseline fishbench`
+const BASELINE: &str = "fishbench";
+
+fn input(size: usize) -> String {
+    (0..size).map(|i| ((i * 7 + 3) % 5 + 1).to_string()).collect::<Vec<_>>().join(",")
+}
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("fishtest", |b| b.iter(|| bench_function()));
+    for days in DAYS {
+        let mut group = c.benchmark_group(format!("day {}", days));
+
+        for size in SIZES {
+            let input = input(size);
+
+            // Every fish is kept around, so only feasible for small populations
+            if days <= 80 && size <= 300 {
+                group.bench_with_input(BenchmarkId::new("part1", size), &input, |b, input| {
+                    b.iter(|| part1::Fishes::from_str(input).unwrap().nth(days as usize - 1).unwrap().len())
+                });
+            }
+
+            // u64 counts overflow a bit after day 400
+            if days <= 256 {
+                group.bench_with_input(BenchmarkId::new("part2", size), &input, |b, input| {
+                    b.iter(|| part2::Fishes::<u64>::from_str(input).unwrap().nth(days as usize - 1).unwrap().unwrap())
+                });
+                group.bench_with_input(BenchmarkId::new("jump_ahead", size), &input, |b, input| {
+                    b.iter(|| part2::Fishes::<u64>::from_str(input).unwrap().jump_ahead(black_box(days)).unwrap())
+                });
+            }
+
+            group.bench_with_input(BenchmarkId::new("population_after", size), &input, |b, input| {
+                let fishes = part2::Fishes::<u64>::from_str(input).unwrap();
+                b.iter(|| fishes.population_after(black_box(days), Some(MODULUS)).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new("BigFishes", size), &input, |b, input| {
+                b.iter(|| BigFishes::from_str(input).unwrap().nth(days as usize - 1).unwrap().unwrap())
+            });
+        }
+
+        group.finish();
+    }
 }
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group! {
+    name = benches;
+    config = Criterion::default().save_baseline(BASELINE.to_string());
+    targets = criterion_benchmark
+}
 criterion_main!(benches);
@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FishError {
+    #[error("Could not parse fish timer")]
+    ParseError(#[from] std::num::ParseIntError),
+    #[error("Timer {0} is longer than any timer in the lifecycle")]
+    InvalidTimer(usize),
+    #[error("Population doesn't fit in the counter on day {0}")]
+    Overflow(u64),
+}
@@ -1,2 +1,7 @@
+pub mod fisherror;
+pub mod lifecycle;
+pub mod lineage;
+pub mod matrix;
 pub mod part1;
 pub mod part2;
+pub mod stochastic;
@@ -0,0 +1,124 @@
+use crate::fisherror::FishError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FishState {
+    // Number of cycles completed since birth, capped at the adult stage
+    pub stage: usize,
+    pub timer: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lifecycle {
+    // Timer of a fish that just reached zero
+    pub reset: usize,
+    // Timer of a fish that was just born
+    pub newborn: usize,
+    // Cycles a young fish goes through before it starts giving birth
+    pub maturity_cycles: usize,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle { reset: 6, newborn: 8, maturity_cycles: 0 }
+    }
+}
+
+impl Lifecycle {
+    pub fn new(reset: usize, newborn: usize) -> Self {
+        Lifecycle { reset, newborn, maturity_cycles: 0 }
+    }
+
+    pub fn with_maturity(self, maturity_cycles: usize) -> Self {
+        Lifecycle { maturity_cycles, ..self }
+    }
+
+    pub fn max_timer(&self) -> usize {
+        self.reset.max(self.newborn)
+    }
+
+    pub fn adult(&self, timer: usize) -> FishState {
+        FishState { stage: self.maturity_cycles, timer }
+    }
+
+    pub fn newborn(&self) -> FishState {
+        FishState { stage: 0, timer: self.newborn }
+    }
+
+    // State of the fish on the next day, with its newborn if it gave birth
+    pub fn step(&self, fish: FishState) -> (FishState, Option<FishState>) {
+        match fish.timer {
+            0 if fish.stage < self.maturity_cycles => (FishState { stage: fish.stage + 1, timer: self.reset }, None),
+            0 => (FishState { stage: fish.stage, timer: self.reset }, Some(self.newborn())),
+            timer => (FishState { stage: fish.stage, timer: timer - 1 }, None),
+        }
+    }
+
+    // Every (stage, timer) pair gets its own index for counting simulations
+    pub fn state_count(&self) -> usize {
+        (self.maturity_cycles + 1) * (self.max_timer() + 1)
+    }
+
+    pub fn index(&self, fish: FishState) -> usize {
+        fish.stage * (self.max_timer() + 1) + fish.timer
+    }
+
+    pub fn state(&self, index: usize) -> FishState {
+        FishState { stage: index / (self.max_timer() + 1), timer: index % (self.max_timer() + 1) }
+    }
+
+    // Initial fish are adults with given timers
+    pub fn parse_timers(&self, s: &str) -> Result<Vec<FishState>, FishError> {
+        s.split(',')
+            .map(|val| {
+                let timer = val.parse::<usize>()?;
+                if timer > self.max_timer() {
+                    return Err(FishError::InvalidTimer(timer));
+                }
+                Ok(self.adult(timer))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_step() {
+        let lifecycle = Lifecycle::default();
+        assert_eq!(lifecycle.step(lifecycle.adult(3)), (lifecycle.adult(2), None));
+        assert_eq!(lifecycle.step(lifecycle.adult(0)), (lifecycle.adult(6), Some(lifecycle.adult(8))));
+    }
+
+    #[test]
+    fn lifecycle_step_maturity() {
+        let lifecycle = Lifecycle::new(2, 3).with_maturity(1);
+        let young = lifecycle.newborn();
+        assert_eq!(young, FishState { stage: 0, timer: 3 });
+
+        let (young, _) = lifecycle.step(FishState { stage: 0, timer: 0 });
+        assert_eq!(young, FishState { stage: 1, timer: 2 });
+
+        let (adult, born) = lifecycle.step(FishState { stage: 1, timer: 0 });
+        assert_eq!(adult, FishState { stage: 1, timer: 2 });
+        assert_eq!(born, Some(lifecycle.newborn()));
+    }
+
+    #[test]
+    fn lifecycle_indexes() {
+        let lifecycle = Lifecycle::new(4, 6).with_maturity(2);
+        assert_eq!(lifecycle.state_count(), 21);
+        for index in 0..lifecycle.state_count() {
+            assert_eq!(lifecycle.index(lifecycle.state(index)), index);
+        }
+    }
+
+    #[test]
+    fn lifecycle_parse_timers() {
+        let lifecycle = Lifecycle::new(2, 3);
+        assert_eq!(lifecycle.parse_timers("0,3"), Ok(vec![lifecycle.adult(0), lifecycle.adult(3)]));
+        assert_eq!(lifecycle.parse_timers("0,4"), Err(FishError::InvalidTimer(4)));
+        assert!(lifecycle.parse_timers("0,a").is_err());
+    }
+}
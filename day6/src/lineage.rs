@@ -0,0 +1,191 @@
+use crate::fisherror::FishError;
+use crate::lifecycle::Lifecycle;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Origin {
+    // Every fish from the input is its own origin, numbered in input order
+    Fish,
+    // Fish from the input with the same timer share an origin, numbered by that timer
+    InitialTimer,
+}
+
+// Counting simulation that remembers which initial fish every fish descends from
+// and how many generations separate them
+pub struct Lineage {
+    // Counts indexed by Lifecycle::index, for every (origin, generation) seen so far
+    counts: BTreeMap<(usize, usize), Vec<u64>>,
+    lifecycle: Lifecycle,
+    day: u64,
+    overflowed: bool,
+}
+
+impl FromStr for Lineage {
+    type Err = FishError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lineage::with_lifecycle(s, Lifecycle::default(), Origin::Fish)
+    }
+}
+
+impl Lineage {
+    pub fn with_lifecycle(s: &str, lifecycle: Lifecycle, origin: Origin) -> Result<Self, FishError> {
+        let mut counts: BTreeMap<(usize, usize), Vec<u64>> = BTreeMap::new();
+
+        for (idx, fish) in lifecycle.parse_timers(s)?.into_iter().enumerate() {
+            let origin = match origin {
+                Origin::Fish => idx,
+                Origin::InitialTimer => fish.timer,
+            };
+            counts.entry((origin, 0)).or_insert_with(|| vec![0; lifecycle.state_count()])[lifecycle.index(fish)] += 1;
+        }
+
+        Ok(Lineage { counts, lifecycle, day: 0, overflowed: false })
+    }
+
+    // None if any count or the whole population doesn't fit in u64
+    fn step(&self) -> Option<BTreeMap<(usize, usize), Vec<u64>>> {
+        let lifecycle = &self.lifecycle;
+        let mut counts: BTreeMap<(usize, usize), Vec<u64>> = BTreeMap::new();
+
+        for (&(origin, generation), states) in &self.counts {
+            for (index, count) in states.iter().enumerate().filter(|(_, count)| **count > 0) {
+                let (next, born) = lifecycle.step(lifecycle.state(index));
+
+                let same = &mut counts.entry((origin, generation)).or_insert_with(|| vec![0; states.len()])[lifecycle.index(next)];
+                *same = same.checked_add(*count)?;
+
+                if let Some(born) = born {
+                    let children = &mut counts.entry((origin, generation + 1)).or_insert_with(|| vec![0; states.len()])[lifecycle.index(born)];
+                    *children = children.checked_add(*count)?;
+                }
+            }
+        }
+
+        counts.values().flatten().try_fold(0_u64, |total, count| total.checked_add(*count))?;
+        Some(counts)
+    }
+
+    // Every sum below is at most the population, which step keeps within u64
+    pub fn population(&self) -> u64 {
+        self.counts.values().flatten().sum()
+    }
+
+    // Fish descending from each origin, including the initial fish themselves
+    pub fn per_origin(&self) -> BTreeMap<usize, u64> {
+        let mut totals = BTreeMap::new();
+        for (&(origin, _), states) in &self.counts {
+            *totals.entry(origin).or_insert(0) += states.iter().sum::<u64>();
+        }
+        totals
+    }
+
+    // Index 0 holds the initial fish, index 1 their children and so on
+    pub fn per_generation(&self) -> Vec<u64> {
+        let depth = self.counts.keys().map(|(_, generation)| generation + 1).max().unwrap_or(0);
+        let mut totals = vec![0; depth];
+        for (&(_, generation), states) in &self.counts {
+            totals[generation] += states.iter().sum::<u64>();
+        }
+        totals
+    }
+
+    pub fn generations_of(&self, origin: usize) -> Vec<u64> {
+        let mut totals = vec![];
+        for (_, generation) in self.counts.range((origin, 0)..(origin + 1, 0)) {
+            totals.push(generation.iter().sum());
+        }
+        totals
+    }
+}
+
+// Same as part2::Fishes, ends with an error on the first day the population doesn't fit in u64
+impl Iterator for Lineage {
+    type Item = Result<u64, FishError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.overflowed {
+            return None;
+        }
+
+        match self.step() {
+            Some(counts) => {
+                self.counts = counts;
+                self.day += 1;
+                Some(Ok(self.population()))
+            },
+            None => {
+                self.overflowed = true;
+                Some(Err(FishError::Overflow(self.day + 1)))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn lineage_matches_part2() -> Result<(), Box<dyn std::error::Error>> {
+        let lineage = Lineage::from_str("3,4,3,1,2")?;
+        let fishes: crate::part2::Fishes = crate::part2::Fishes::from_str("3,4,3,1,2")?;
+        assert_eq!(lineage.take(100).collect::<Result<Vec<_>, _>>()?, fishes.take(100).collect::<Result<Vec<_>, _>>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lineage_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let input = vec!["1"; 300].join(",");
+        let lineage = Lineage::with_lifecycle(&input, Lifecycle::default(), Origin::InitialTimer)?;
+        let fishes: crate::part2::Fishes = crate::part2::Fishes::from_str(&input)?;
+
+        let lineage = lineage.collect::<Vec<_>>();
+        assert_eq!(lineage, fishes.collect::<Vec<_>>());
+        assert!(matches!(lineage.last(), Some(Err(FishError::Overflow(_)))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn lineage_per_origin() -> Result<(), Box<dyn std::error::Error>> {
+        let mut lineage = Lineage::from_str("3,4,3,1,2")?;
+        assert_eq!(lineage.nth(17), Some(Ok(26)));
+
+        // Each fish with timer 3 ends up as 5 fish on day 18, the one with timer 1 as 7
+        let per_origin = lineage.per_origin();
+        assert_eq!(per_origin.values().sum::<u64>(), 26);
+        assert_eq!(per_origin[&0], per_origin[&2]);
+        assert_eq!(per_origin.values().copied().collect::<Vec<_>>(), vec![5, 4, 5, 7, 5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lineage_per_initial_timer() -> Result<(), Box<dyn std::error::Error>> {
+        let mut lineage = Lineage::with_lifecycle("3,4,3,1,2", Lifecycle::default(), Origin::InitialTimer)?;
+        lineage.nth(17);
+
+        let per_origin = lineage.per_origin();
+        assert_eq!(per_origin.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(per_origin[&3], 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lineage_per_generation() -> Result<(), Box<dyn std::error::Error>> {
+        let mut lineage = Lineage::from_str("0")?;
+        assert_eq!(lineage.per_generation(), vec![1]);
+
+        // Born on days 1, 8 and 15, the first child has its own child on day 10
+        lineage.nth(14);
+        assert_eq!(lineage.per_generation(), vec![1, 3, 1]);
+        assert_eq!(lineage.generations_of(0), vec![1, 3, 1]);
+        assert_eq!(lineage.generations_of(1), Vec::<u64>::new());
+
+        Ok(())
+    }
+}
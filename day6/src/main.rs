@@ -10,7 +10,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Total number of fishes: {result}");
 
         let fishes: part2::Fishes = part2::Fishes::from_str(input.trim())?;
-        let result = fishes.skip(255).take(1).next().unwrap();
+        let result = fishes.skip(255).take(1).next().unwrap()?;
         println!("Total number of fishes after 256 days: {result}");
 
         Ok(())
@@ -0,0 +1,96 @@
+// Square matrix of counts, used to advance fish populations many days at once
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix(Vec<Vec<u64>>);
+
+// Sum of products, reduced by the modulus if there is one. None when the exact value doesn't fit
+// or the modulus is 0
+fn dot(lhs: impl Iterator<Item = u64>, rhs: impl Iterator<Item = u64>, modulus: Option<u64>) -> Option<u64> {
+    match modulus {
+        Some(m) => {
+            let sum = lhs.zip(rhs).try_fold(0_u128, |acc, (a, b)| (acc + a as u128 * b as u128).checked_rem(m as u128))?;
+            Some(sum as u64)
+        },
+        None => lhs.zip(rhs).try_fold(0_u64, |acc, (a, b)| acc.checked_add(a.checked_mul(b)?)),
+    }
+}
+
+impl Matrix {
+    pub fn new(rows: Vec<Vec<u64>>) -> Self {
+        Matrix(rows)
+    }
+
+    pub fn identity(size: usize) -> Self {
+        Matrix((0..size).map(|i| (0..size).map(|j| u64::from(i == j)).collect()).collect())
+    }
+
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn mul(&self, rhs: &Matrix, modulus: Option<u64>) -> Option<Matrix> {
+        let size = self.size();
+        let mut rows = vec![vec![0; size]; size];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = dot(self.0[i].iter().copied(), rhs.0.iter().map(|rhs_row| rhs_row[j]), modulus)?;
+            }
+        }
+
+        Some(Matrix(rows))
+    }
+
+    // Exponentiation by squaring, None for a modulus of 0
+    pub fn pow(&self, mut exp: u64, modulus: Option<u64>) -> Option<Matrix> {
+        if modulus == Some(0) {
+            return None;
+        }
+
+        let mut result = Matrix::identity(self.size());
+        let mut base = self.clone();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base, modulus)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base, modulus)?;
+            }
+        }
+
+        if let Some(m) = modulus {
+            result.0.iter_mut().flatten().for_each(|cell| *cell %= m);
+        }
+
+        Some(result)
+    }
+
+    pub fn apply(&self, vector: &[u64], modulus: Option<u64>) -> Option<Vec<u64>> {
+        self.0.iter().map(|row| dot(row.iter().copied(), vector.iter().copied(), modulus)).collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_pow() {
+        let fibonacci = Matrix::new(vec![vec![1, 1], vec![1, 0]]);
+        assert_eq!(fibonacci.pow(0, None), Some(Matrix::identity(2)));
+        assert_eq!(fibonacci.pow(10, None), Some(Matrix::new(vec![vec![89, 55], vec![55, 34]])));
+        assert_eq!(fibonacci.pow(10, Some(10)), Some(Matrix::new(vec![vec![9, 5], vec![5, 4]])));
+        assert_eq!(fibonacci.pow(100, None), None);
+        assert_eq!(fibonacci.pow(0, Some(0)), None);
+        assert_eq!(fibonacci.pow(10, Some(0)), None);
+        assert_eq!(fibonacci.apply(&[1, 1], Some(0)), None);
+    }
+
+    #[test]
+    fn matrix_apply() {
+        let swap = Matrix::new(vec![vec![0, 1], vec![1, 0]]);
+        assert_eq!(swap.apply(&[3, 7], None), Some(vec![7, 3]));
+        assert_eq!(swap.apply(&[3, 7], Some(5)), Some(vec![2, 3]));
+    }
+}
@@ -1,14 +1,20 @@
+use crate::fisherror::FishError;
+use crate::lifecycle::{FishState, Lifecycle};
 use std::str::FromStr;
 
-pub struct Fishes(Vec<u64>);
+pub struct Fishes(Vec<FishState>, Lifecycle);
 
 impl FromStr for Fishes {
-    type Err = std::num::ParseIntError;
+    type Err = FishError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let fishes = s.split(',').map(|val| val.parse::<u64>()).collect::<Result<Vec<_>, _>>()?;
+        Fishes::with_lifecycle(s, Lifecycle::default())
+    }
+}
 
-        Ok(Fishes(fishes))
+impl Fishes {
+    pub fn with_lifecycle(s: &str, lifecycle: Lifecycle) -> Result<Self, FishError> {
+        Ok(Fishes(lifecycle.parse_timers(s)?, lifecycle))
     }
 }
 
@@ -16,31 +22,20 @@ impl Iterator for Fishes {
     type Item = Vec<u64>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut born_fish_counter = 0;
+        let mut born_fishes = vec![];
 
-        for fish_days_left in self.0.iter_mut() {
-            *fish_days_left = match *fish_days_left {
-                0 => { born_fish_counter += 1; 6 },
-                _ => { *fish_days_left -1 },
-            }
+        for fish in self.0.iter_mut() {
+            let (next, born) = self.1.step(*fish);
+            *fish = next;
+            born_fishes.extend(born);
         }
 
-        for _ in 0..born_fish_counter {
-            self.0.push(8);
-        }
+        self.0.append(&mut born_fishes);
 
-        Some(self.0.clone())
+        Some(self.0.iter().map(|fish| fish.timer as u64).collect())
     }
 }
 
-pub fn bench_function() -> Result<(), Box<dyn std::error::Error>> {
-    let input = "3,4,3,1,2";
-    let fishes: Fishes = Fishes::from_str(input)?;
-    assert_eq!(fishes.skip(100).take(1).next().unwrap().len(), 36920);
-
-    Ok(())
-}
-
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -65,6 +60,31 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn part1_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
+        let mut fishes = Fishes::with_lifecycle("0,2", Lifecycle::new(2, 3))?;
+        assert_eq!(fishes.next(), Some(vec![2,1,3]));
+        assert_eq!(fishes.next(), Some(vec![1,0,2]));
+        assert_eq!(fishes.next(), Some(vec![0,2,1,3]));
+
+        let mut fishes = Fishes::with_lifecycle("0", Lifecycle::new(1, 1).with_maturity(1))?;
+        assert_eq!(fishes.next(), Some(vec![1,1]));
+        assert_eq!(fishes.next(), Some(vec![0,0]));
+        assert_eq!(fishes.next(), Some(vec![1,1,1]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn part1_matches_part2() -> Result<(), Box<dyn std::error::Error>> {
+        let lifecycle = Lifecycle::new(3, 5).with_maturity(2);
+        let naive = Fishes::with_lifecycle("3,4,3,1,2", lifecycle.clone())?.map(|fishes| fishes.len() as u64);
+        let counting: crate::part2::Fishes = crate::part2::Fishes::with_lifecycle("3,4,3,1,2", lifecycle)?;
+        assert_eq!(naive.take(60).collect::<Vec<_>>(), counting.take(60).collect::<Result<Vec<_>, _>>()?);
+
+        Ok(())
+    }
+
     #[test]
     fn part1_after_80_days() -> Result<(), Box<dyn std::error::Error>> {
         let input = "3,4,3,1,2";
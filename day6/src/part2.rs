@@ -1,29 +1,128 @@
+pub mod query;
+
+use crate::fisherror::FishError;
+use crate::lifecycle::Lifecycle;
+use crate::matrix::Matrix;
+use num_bigint::BigUint;
+use num_traits::{CheckedAdd, One, Zero};
 use std::str::FromStr;
 
-// Each index represents days left
-pub struct Fishes([u64; 9]);
+// Anything fish can be counted with, u64 overflows around day 450 while BigUint never does
+pub trait Count: Clone + Zero + One + CheckedAdd {}
+
+impl<N: Clone + Zero + One + CheckedAdd> Count for N {}
+
+pub struct Fishes<N: Count = u64> {
+    // Number of fish in each lifecycle state, indexed by Lifecycle::index
+    counts: Vec<N>,
+    lifecycle: Lifecycle,
+    day: u64,
+    overflowed: bool,
+}
+
+pub type BigFishes = Fishes<BigUint>;
 
-impl FromStr for Fishes {
-    type Err = std::num::ParseIntError;
+impl<N: Count> FromStr for Fishes<N> {
+    type Err = FishError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut retarr = [0; 9];
-        let fishes = s.split(',').map(|val| val.parse::<usize>()).collect::<Result<Vec<_>, _>>()?;
-        for fish in fishes {
-            retarr[fish] += 1;
+        Fishes::with_lifecycle(s, Lifecycle::default())
+    }
+}
+
+impl<N: Count> Fishes<N> {
+    pub fn with_lifecycle(s: &str, lifecycle: Lifecycle) -> Result<Self, FishError> {
+        let mut counts = vec![N::zero(); lifecycle.state_count()];
+        for fish in lifecycle.parse_timers(s)? {
+            let count = &mut counts[lifecycle.index(fish)];
+            *count = count.checked_add(&N::one()).ok_or(FishError::Overflow(0))?;
         }
 
-        Ok(Fishes(retarr))
+        Ok(Fishes { counts, lifecycle, day: 0, overflowed: false })
+    }
+
+    fn total(counts: &[N]) -> Option<N> {
+        counts.iter().try_fold(N::zero(), |acc, count| acc.checked_add(count))
+    }
+
+    // Counts for the next day and their total, None if they don't fit in N
+    fn step(&self) -> Option<(Vec<N>, N)> {
+        let lifecycle = &self.lifecycle;
+        let mut counts = vec![N::zero(); self.counts.len()];
+
+        for (index, count) in self.counts.iter().enumerate().filter(|(_, count)| !count.is_zero()) {
+            let (next, born) = lifecycle.step(lifecycle.state(index));
+            counts[lifecycle.index(next)] = counts[lifecycle.index(next)].checked_add(count)?;
+            if let Some(born) = born {
+                counts[lifecycle.index(born)] = counts[lifecycle.index(born)].checked_add(count)?;
+            }
+        }
+
+        let total = Fishes::total(&counts)?;
+        Some((counts, total))
     }
 }
 
-impl Iterator for Fishes {
-    type Item = u64;
+impl Fishes<u64> {
+    // Column j says which states the fish in state j end up in the next day
+    fn transition(&self) -> Matrix {
+        let lifecycle = &self.lifecycle;
+        let size = lifecycle.state_count();
+        let mut rows = vec![vec![0; size]; size];
+
+        for (from, (next, born)) in (0..size).map(|index| lifecycle.step(lifecycle.state(index))).enumerate() {
+            rows[lifecycle.index(next)][from] += 1;
+            if let Some(born) = born {
+                rows[lifecycle.index(born)][from] += 1;
+            }
+        }
+
+        Matrix::new(rows)
+    }
+
+    // Population after given number of days, None if it doesn't fit in u64 without a modulus.
+    // A modulus of 0 is rejected with None as well
+    pub fn population_after(&self, days: u64, modulus: Option<u64>) -> Option<u64> {
+        if modulus == Some(0) {
+            return None;
+        }
+        let state = self.transition().pow(days, modulus)?.apply(&self.counts, modulus)?;
+        match modulus {
+            Some(m) => Some(state.iter().fold(0, |acc, count| ((acc as u128 + *count as u128) % m as u128) as u64)),
+            None => Fishes::total(&state),
+        }
+    }
+
+    // Moves the simulation forward, leaving it unchanged if the population would overflow
+    pub fn jump_ahead(&mut self, days: u64) -> Option<u64> {
+        let state = self.transition().pow(days, None)?.apply(&self.counts, None)?;
+        let total = Fishes::total(&state)?;
+        self.counts = state;
+        self.day += days;
+        Some(total)
+    }
+}
+
+// Ends with an error on the first day the population doesn't fit in N
+impl<N: Count> Iterator for Fishes<N> {
+    type Item = Result<N, FishError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.rotate_left(1);
-        self.0[6] += self.0[8];
-        Some(self.0.iter().sum())
+        if self.overflowed {
+            return None;
+        }
+
+        match self.step() {
+            Some((counts, total)) => {
+                self.counts = counts;
+                self.day += 1;
+                Some(Ok(total))
+            },
+            None => {
+                self.overflowed = true;
+                Some(Err(FishError::Overflow(self.day + 1)))
+            },
+        }
     }
 }
 
@@ -35,7 +134,7 @@ pub mod tests {
     fn part2_from_str() -> Result<(), Box<dyn std::error::Error>> {
         let input = "0,0,5,5,5,8";
         let fishes: Fishes = Fishes::from_str(input)?;
-        assert_eq!(fishes.0, [2, 0, 0, 0, 0, 3, 0, 0, 1]);
+        assert_eq!(fishes.counts, [2, 0, 0, 0, 0, 3, 0, 0, 1]);
 
         Ok(())
     }
@@ -44,9 +143,9 @@ pub mod tests {
     fn part2_next() -> Result<(), Box<dyn std::error::Error>> {
         let input = "3,4,3,1,2";
         let mut fishes: Fishes = Fishes::from_str(input)?;
-        assert_eq!(fishes.next(), Some(5));
-        assert_eq!(fishes.next(), Some(6));
-        assert_eq!(fishes.next(), Some(7));
+        assert_eq!(fishes.next(), Some(Ok(5)));
+        assert_eq!(fishes.next(), Some(Ok(6)));
+        assert_eq!(fishes.next(), Some(Ok(7)));
 
         Ok(())
     }
@@ -55,7 +154,7 @@ pub mod tests {
     fn part2_after_18_days() -> Result<(), Box<dyn std::error::Error>> {
         let input = "3,4,3,1,2";
         let fishes: Fishes = Fishes::from_str(input)?;
-        assert_eq!(fishes.skip(17).take(1).next(), Some(26));
+        assert_eq!(fishes.skip(17).take(1).next(), Some(Ok(26)));
 
         Ok(())
     }
@@ -64,7 +163,102 @@ pub mod tests {
     fn part2_after_80_days() -> Result<(), Box<dyn std::error::Error>> {
         let input = "3,4,3,1,2";
         let fishes: Fishes = Fishes::from_str(input)?;
-        assert_eq!(fishes.skip(79).take(1).next(), Some(5934));
+        assert_eq!(fishes.skip(79).take(1).next(), Some(Ok(5934)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
+        let mut fishes: Fishes = Fishes::with_lifecycle("2", Lifecycle::new(2, 4))?;
+        assert_eq!(fishes.by_ref().take(10).collect::<Result<Vec<_>, _>>()?, vec![1, 1, 2, 2, 2, 3, 3, 4, 5, 5]);
+
+        let mut fishes: Fishes = Fishes::with_lifecycle("0", Lifecycle::new(1, 1).with_maturity(1))?;
+        assert_eq!(fishes.by_ref().take(6).collect::<Result<Vec<_>, _>>()?, vec![2, 2, 3, 3, 5, 5]);
+        assert_eq!(fishes.population_after(2, None), Some(8));
+
+        assert!(Fishes::<u64>::with_lifecycle("5", Lifecycle::new(2, 4)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_population_after() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "3,4,3,1,2";
+        let fishes: Fishes = Fishes::from_str(input)?;
+        assert_eq!(fishes.population_after(0, None), Some(5));
+        assert_eq!(fishes.population_after(18, None), Some(26));
+        assert_eq!(fishes.population_after(80, None), Some(5934));
+        assert_eq!(fishes.population_after(256, None), Some(26984457539));
+
+        for (day, population) in Fishes::from_str(input)?.take(300).enumerate() {
+            assert_eq!(fishes.population_after(day as u64 + 1, None), Some(population?));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "3,4,3,1,2";
+        let mut fishes: Fishes = Fishes::from_str(input)?;
+        let last_day = fishes.by_ref().take_while(Result::is_ok).count() as u64;
+        assert!(last_day > 400);
+        assert_eq!(fishes.next(), None);
+
+        let fishes: Fishes = Fishes::from_str(input)?;
+        assert!(fishes.population_after(last_day, None).is_some());
+        assert!(fishes.population_after(last_day + 1, None).is_none());
+
+        let mut fishes: Fishes = Fishes::from_str(input)?;
+        assert_eq!(fishes.nth(last_day as usize), Some(Err(FishError::Overflow(last_day + 1))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_big_integers() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "3,4,3,1,2";
+        let fishes: Fishes = Fishes::from_str(input)?;
+        let big_fishes: BigFishes = BigFishes::from_str(input)?;
+
+        for (day, population) in big_fishes.take(1000).enumerate() {
+            let population = population?;
+            match fishes.population_after(day as u64 + 1, None) {
+                Some(exact) => assert_eq!(population, BigUint::from(exact)),
+                None => assert!(population > BigUint::from(u64::MAX)),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_population_after_modulus() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "3,4,3,1,2";
+        let fishes: Fishes = Fishes::from_str(input)?;
+        assert_eq!(fishes.population_after(256, Some(1_000_000_007)), Some(26984457539 % 1_000_000_007));
+        assert!(fishes.population_after(1_000_000_000_000, Some(1_000_000_007)).is_some());
+        assert_eq!(fishes.population_after(1_000_000_000, None), None);
+        assert_eq!(fishes.population_after(0, Some(0)), None);
+        assert_eq!(fishes.population_after(256, Some(0)), None);
+
+        let modulus = u64::MAX - 58;
+        let exact = BigFishes::from_str("3,4,3,1,2")?.nth(499).unwrap()?;
+        let expected = u64::try_from(exact % modulus)?;
+        assert_eq!(fishes.population_after(500, Some(modulus)), Some(expected));
+
+        Ok(())
+    }
+
+    #[test]
+    fn part2_jump_ahead() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "3,4,3,1,2";
+        let mut fishes: Fishes = Fishes::from_str(input)?;
+        assert_eq!(fishes.jump_ahead(18), Some(26));
+        assert_eq!(fishes.next(), Some(Ok(26 + 3)));
+        assert_eq!(fishes.jump_ahead(1_000_000), None);
+        assert_eq!(fishes.jump_ahead(61), Some(5934));
 
         Ok(())
     }
@@ -0,0 +1,136 @@
+use super::*;
+
+impl<N: Count> Fishes<N> {
+    // Number of fish with each timer today, regardless of their maturity
+    pub fn histogram(&self) -> Vec<N> {
+        let mut histogram = vec![N::zero(); self.lifecycle.max_timer() + 1];
+        for (index, count) in self.counts.iter().enumerate() {
+            let timer = self.lifecycle.state(index).timer;
+            histogram[timer] = histogram[timer].clone() + count.clone();
+        }
+        histogram
+    }
+
+    pub fn day(&self) -> u64 {
+        self.day
+    }
+}
+
+impl Fishes<u64> {
+    // None if any count doesn't fit in u64
+    pub fn histogram_after(&self, days: u64) -> Option<Vec<u64>> {
+        let counts = self.transition().pow(days, None)?.apply(&self.counts, None)?;
+        let mut histogram = vec![0_u64; self.lifecycle.max_timer() + 1];
+        for (index, count) in counts.iter().enumerate() {
+            let timer = self.lifecycle.state(index).timer;
+            histogram[timer] = histogram[timer].checked_add(*count)?;
+        }
+        Some(histogram)
+    }
+
+    pub fn fish_with_timer(&self, timer: usize, days: u64) -> Option<u64> {
+        self.histogram_after(days)?.get(timer).copied()
+    }
+
+    // Days from now until the population first goes over the threshold.
+    // Population never shrinks, so it doubles the step until it's above and then bisects.
+    pub fn first_day_above(&self, threshold: u64) -> Option<u64> {
+        // Populations too big for u64 are above any threshold
+        let above = |days: u64| self.population_after(days, None).is_none_or(|population| population > threshold);
+
+        if above(0) {
+            return Some(0);
+        }
+        if self.counts.iter().all(|count| *count == 0) {
+            return None;
+        }
+
+        let mut high = 1;
+        while !above(high) {
+            high *= 2;
+        }
+
+        let mut low = high / 2;
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if above(mid) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        Some(high)
+    }
+
+    // Ratio between populations on the day after and on the given day
+    pub fn growth_rate(&self, day: u64) -> Option<f64> {
+        let today = self.population_after(day, None)?;
+        let tomorrow = self.population_after(day + 1, None)?;
+        if today == 0 {
+            return None;
+        }
+        Some(tomorrow as f64 / today as f64)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram() -> Result<(), Box<dyn std::error::Error>> {
+        let fishes: Fishes = Fishes::from_str("3,4,3,1,2")?;
+        assert_eq!(fishes.histogram(), vec![0, 1, 1, 2, 1, 0, 0, 0, 0]);
+
+        // 6,0,6,4,5,6,0,1,1,2,6,0,1,1,1,2,2,3,3,4,6,7,8,8,8,8 after 18 days
+        assert_eq!(fishes.histogram_after(18), Some(vec![3, 5, 3, 2, 2, 1, 5, 1, 4]));
+        assert_eq!(fishes.fish_with_timer(6, 18), Some(5));
+        assert_eq!(fishes.fish_with_timer(9, 18), None);
+
+        let mut fishes = fishes;
+        fishes.jump_ahead(18);
+        assert_eq!(fishes.day(), 18);
+        assert_eq!(fishes.histogram(), vec![3, 5, 3, 2, 2, 1, 5, 1, 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_with_maturity() -> Result<(), Box<dyn std::error::Error>> {
+        let fishes: Fishes = Fishes::with_lifecycle("0", Lifecycle::new(1, 1).with_maturity(1))?;
+        assert_eq!(fishes.histogram_after(3), Some(vec![0, 3]));
+        assert_eq!(fishes.histogram_after(4), Some(vec![3, 0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn first_day_above() -> Result<(), Box<dyn std::error::Error>> {
+        let fishes: Fishes = Fishes::from_str("3,4,3,1,2")?;
+        assert_eq!(fishes.first_day_above(4), Some(0));
+        assert_eq!(fishes.first_day_above(5), Some(2));
+        assert_eq!(fishes.first_day_above(25), Some(18));
+        assert_eq!(fishes.first_day_above(5933), Some(80));
+        assert_eq!(fishes.first_day_above(5934), Some(81));
+
+        let day = fishes.first_day_above(1_000_000_000_000_000).unwrap();
+        assert!(fishes.population_after(day - 1, None).unwrap() <= 1_000_000_000_000_000);
+        assert!(fishes.population_after(day, None).unwrap() > 1_000_000_000_000_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn growth_rate() -> Result<(), Box<dyn std::error::Error>> {
+        let fishes: Fishes = Fishes::from_str("3,4,3,1,2")?;
+        assert_eq!(fishes.growth_rate(0), Some(1.0));
+        assert_eq!(fishes.growth_rate(2), Some(7.0 / 6.0));
+
+        // Settles to the largest eigenvalue of the transition matrix
+        let rate = fishes.growth_rate(300).unwrap();
+        assert!((rate - 1.0910).abs() < 5e-3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::fisherror::FishError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+// Number of days, always at least one when sampled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Delay {
+    Fixed(u64),
+    // Both ends inclusive
+    Uniform(u64, u64),
+    // Rounded to whole days
+    Normal { mean: f64, std_dev: f64 },
+}
+
+impl Delay {
+    fn sample<R: Rng>(&self, rng: &mut R) -> u64 {
+        let days = match *self {
+            Delay::Fixed(days) => days,
+            Delay::Uniform(min, max) => rng.gen_range(min.min(max)..=max.max(min)),
+            Delay::Normal { mean, std_dev } => match Normal::new(mean, std_dev) {
+                Ok(normal) => normal.sample(rng).round().max(1.0) as u64,
+                Err(_) => mean.round().max(1.0) as u64,
+            },
+        };
+        days.max(1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StochasticModel {
+    // Days between two births of the same fish
+    pub reproduction: Delay,
+    // Days from birth to the first birth of a new fish
+    pub newborn: Delay,
+    // Chance for every fish to die on any given day
+    pub death_probability: f64,
+}
+
+impl Default for StochasticModel {
+    fn default() -> Self {
+        StochasticModel { reproduction: Delay::Fixed(7), newborn: Delay::Fixed(9), death_probability: 0.0 }
+    }
+}
+
+// Simulates every fish on its own, so it gets slow once populations grow large
+pub struct StochasticFishes {
+    timers: Vec<u64>,
+    model: StochasticModel,
+    rng: StdRng,
+}
+
+impl StochasticFishes {
+    pub fn new(s: &str, model: StochasticModel, seed: u64) -> Result<Self, FishError> {
+        let timers = s.split(',').map(|val| val.parse::<u64>()).collect::<Result<Vec<_>, _>>()?;
+        Ok(StochasticFishes { timers, model, rng: StdRng::seed_from_u64(seed) })
+    }
+}
+
+impl Iterator for StochasticFishes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let death_probability = self.model.death_probability.clamp(0.0, 1.0);
+        let rng = &mut self.rng;
+        self.timers.retain(|_| !rng.gen_bool(death_probability));
+
+        let mut born_fish_counter = 0;
+        for timer in self.timers.iter_mut() {
+            *timer = match *timer {
+                0 => { born_fish_counter += 1; self.model.reproduction.sample(&mut self.rng) - 1 },
+                _ => *timer - 1,
+            }
+        }
+
+        for _ in 0..born_fish_counter {
+            let timer = self.model.newborn.sample(&mut self.rng) - 1;
+            self.timers.push(timer);
+        }
+
+        Some(self.timers.len() as u64)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayStats {
+    pub mean: f64,
+    // Requested percentiles paired with the population at each of them
+    pub percentiles: Vec<(f64, u64)>,
+}
+
+// Nearest rank on sorted values
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Statistics over independent runs for every day from 1 to `days`. Run i uses seed + i
+pub fn monte_carlo(s: &str, model: &StochasticModel, days: usize, runs: usize, seed: u64, percentiles: &[f64]) -> Result<Vec<DayStats>, FishError> {
+    let mut populations: Vec<Vec<u64>> = vec![Vec::with_capacity(runs); days];
+
+    for run in 0..runs {
+        let fishes = StochasticFishes::new(s, model.clone(), seed.wrapping_add(run as u64))?;
+        for (day, population) in fishes.take(days).enumerate() {
+            populations[day].push(population);
+        }
+    }
+
+    Ok(populations.into_iter()
+        .map(|mut day| {
+            day.sort_unstable();
+            let mean = day.iter().sum::<u64>() as f64 / day.len().max(1) as f64;
+            let percentiles = if day.is_empty() {
+                vec![]
+            } else {
+                percentiles.iter().map(|p| (*p, percentile(&day, *p))).collect()
+            };
+            DayStats { mean, percentiles }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn fixed_model_matches_part1() -> Result<(), Box<dyn std::error::Error>> {
+        let stochastic = StochasticFishes::new("3,4,3,1,2", StochasticModel::default(), 42)?;
+        let naive = crate::part1::Fishes::from_str("3,4,3,1,2")?.map(|fishes| fishes.len() as u64);
+        assert_eq!(stochastic.take(80).collect::<Vec<_>>(), naive.take(80).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn same_seed_same_results() -> Result<(), Box<dyn std::error::Error>> {
+        let model = StochasticModel {
+            reproduction: Delay::Uniform(5, 9),
+            newborn: Delay::Normal { mean: 9.0, std_dev: 1.5 },
+            death_probability: 0.01,
+        };
+
+        let first = StochasticFishes::new("3,4,3,1,2", model.clone(), 7)?.take(60).collect::<Vec<_>>();
+        let second = StochasticFishes::new("3,4,3,1,2", model.clone(), 7)?.take(60).collect::<Vec<_>>();
+        let other = StochasticFishes::new("3,4,3,1,2", model, 8)?.take(60).collect::<Vec<_>>();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+
+        Ok(())
+    }
+
+    #[test]
+    fn certain_death() -> Result<(), Box<dyn std::error::Error>> {
+        let model = StochasticModel { death_probability: 1.0, ..Default::default() };
+        let mut fishes = StochasticFishes::new("3,4,3,1,2", model, 0)?;
+        assert_eq!(fishes.next(), Some(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn monte_carlo_bands() -> Result<(), Box<dyn std::error::Error>> {
+        let stats = monte_carlo("3,4,3,1,2", &StochasticModel::default(), 18, 10, 1, &[5.0, 50.0, 95.0])?;
+        assert_eq!(stats.len(), 18);
+        assert_eq!(stats[17], DayStats { mean: 26.0, percentiles: vec![(5.0, 26), (50.0, 26), (95.0, 26)] });
+
+        let model = StochasticModel { reproduction: Delay::Uniform(6, 8), death_probability: 0.02, ..Default::default() };
+        let stats = monte_carlo("3,4,3,1,2", &model, 80, 50, 1, &[5.0, 50.0, 95.0])?;
+        let last = &stats[79];
+        assert!(last.percentiles[0].1 <= last.percentiles[1].1);
+        assert!(last.percentiles[1].1 <= last.percentiles[2].1);
+        assert!(last.mean >= last.percentiles[0].1 as f64 && last.mean <= last.percentiles[2].1 as f64);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nearest_rank_percentile() {
+        let sorted = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(percentile(&sorted, 0.0), 1);
+        assert_eq!(percentile(&sorted, 50.0), 5);
+        assert_eq!(percentile(&sorted, 95.0), 10);
+        assert_eq!(percentile(&sorted, 100.0), 10);
+    }
+}
@@ -0,0 +1,129 @@
+// Fuel a single crab burns to move the given distance
+pub trait CostFunction {
+    fn cost(&self, distance: u64) -> u128;
+
+    // Convex costs that never decrease with distance allow a ternary search
+    fn is_convex(&self) -> bool {
+        false
+    }
+}
+
+pub struct Linear;
+
+impl CostFunction for Linear {
+    fn cost(&self, distance: u64) -> u128 {
+        distance as u128
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+pub struct Triangular;
+
+impl CostFunction for Triangular {
+    fn cost(&self, distance: u64) -> u128 {
+        let distance = distance as u128;
+        distance * (distance + 1) / 2
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+pub struct Quadratic;
+
+impl CostFunction for Quadratic {
+    fn cost(&self, distance: u64) -> u128 {
+        let distance = distance as u128;
+        distance * distance
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// Never more than `cap` fuel, whatever the distance
+pub struct Capped<C: CostFunction> {
+    pub cost: C,
+    pub cap: u128,
+}
+
+impl<C: CostFunction> CostFunction for Capped<C> {
+    fn cost(&self, distance: u64) -> u128 {
+        self.cost.cost(distance).min(self.cap)
+    }
+}
+
+// Marks a cost function as convex and nondecreasing
+pub struct Convex<C: CostFunction>(pub C);
+
+impl<C: CostFunction> CostFunction for Convex<C> {
+    fn cost(&self, distance: u64) -> u128 {
+        self.0.cost(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(u64) -> u128> CostFunction for F {
+    fn cost(&self, distance: u64) -> u128 {
+        self(distance)
+    }
+}
+
+fn argmin<F: Fn(u64) -> u128>(low: u64, high: u64, f: F) -> (u64, u128) {
+    (low..=high).map(|x| (x, f(x))).min_by_key(|(_, value)| *value).unwrap()
+}
+
+// A position in `low..=high` where `f` is smallest, by ternary search when `f` is convex
+pub fn minimize<F: Fn(u64) -> u128>(mut low: u64, mut high: u64, convex: bool, f: F) -> (u64, u128) {
+    if !convex {
+        return argmin(low, high, f);
+    }
+
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+        match f(left).cmp(&f(right)) {
+            std::cmp::Ordering::Less => high = right - 1,
+            std::cmp::Ordering::Greater => low = left + 1,
+            std::cmp::Ordering::Equal => { low = left; high = right; },
+        }
+    }
+
+    argmin(low, high, f)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn costs() {
+        assert_eq!(Linear.cost(11), 11);
+        assert_eq!(Triangular.cost(11), 66);
+        assert_eq!(Quadratic.cost(11), 121);
+        assert_eq!(Capped { cost: Quadratic, cap: 50 }.cost(11), 50);
+        assert_eq!((|d: u64| d as u128 * 3).cost(11), 33);
+        assert!(Convex(|d: u64| d as u128 * 3).is_convex());
+        assert!(!Capped { cost: Linear, cap: 5 }.is_convex());
+    }
+
+    #[test]
+    fn ternary_matches_exhaustive() {
+        for target in [0u64, 1, 2, 7, 50, 99, 100] {
+            let f = |x: u64| (x.abs_diff(target) as u128).pow(2);
+            assert_eq!(minimize(0, 100, true, f), (target, 0));
+            assert_eq!(minimize(0, 100, true, f), minimize(0, 100, false, f));
+        }
+
+        let plateau = |x: u64| x.abs_diff(50).saturating_sub(10) as u128;
+        assert_eq!(minimize(0, 100, true, plateau).1, 0);
+    }
+}
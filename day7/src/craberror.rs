@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CrabError {
+    #[error("Could not parse crab number")]
+    ParseError(#[from] std::num::ParseIntError),
+    #[error("Unknown cost model {0}")]
+    UnknownCostModel(String),
+    #[error("Unexpected fields after the weight in {0}")]
+    TrailingFields(String),
+    #[error("No crabs in the input")]
+    EmptyInput,
+    #[error("Empty field at byte {0}")]
+    EmptyField(usize),
+    #[error("Expected an x,y pair")]
+    NotAPair,
+    #[error("Invalid crab {token:?} at byte {offset}")]
+    InvalidToken { offset: usize, token: String, source: Box<CrabError> },
+}
@@ -1,32 +1,97 @@
+pub mod cost;
+pub mod craberror;
+pub mod mixed;
+pub mod optimum;
+pub mod plane;
+
+use cost::{minimize, CostFunction};
+use craberror::CrabError;
 use std::str::FromStr;
 
+// Byte offset of a token sliced out of `s`
+fn offset(s: &str, token: &str) -> usize {
+    token.as_ptr() as usize - s.as_ptr() as usize
+}
+
+// Parses every token between whitespace, and commas too if `commas` is set. Two commas
+// with nothing between them leave an empty field. Errors point at the byte offset of the token
+pub(crate) fn parse_list<T, E, P>(s: &str, commas: bool, parse: P) -> Result<Vec<T>, CrabError>
+where
+    P: Fn(&str) -> Result<T, E>,
+    E: Into<CrabError>,
+{
+    if s.split(|c: char| (commas && c == ',') || c.is_whitespace()).all(str::is_empty) {
+        return Err(CrabError::EmptyInput);
+    }
+
+    let fields: Vec<&str> = if commas { s.split(',').collect() } else { vec![s] };
+    let mut items = vec![];
+    for field in fields {
+        if field.trim().is_empty() {
+            return Err(CrabError::EmptyField(offset(s, field)));
+        }
+        for token in field.split_whitespace() {
+            let item = parse(token).map_err(|source| CrabError::InvalidToken {
+                offset: offset(s, token),
+                token: token.to_string(),
+                source: Box::new(source.into()),
+            })?;
+            items.push(item);
+        }
+    }
+
+    Ok(items)
+}
+
+// Never empty, so there always is a cheapest position
 pub struct Crabs(Vec<u64>);
 
 impl FromStr for Crabs {
-    type Err = std::num::ParseIntError;
+    type Err = CrabError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let crabs = s.split(',').map(|val| val.parse::<u64>()).collect::<Result<Vec<_>, _>>()?;
+        let crabs = parse_list(s, true, u64::from_str)?;
 
         Ok(Crabs(crabs))
     }
 }
 
+fn triangular(distance: u64) -> u128 {
+    let distance = distance as u128;
+    distance * (distance + 1) / 2
+}
+
 impl Crabs {
-    fn part1_cost_to(&self, position: u64) -> u64 {
-        self.0.iter().map(|pos| pos.abs_diff(position)).sum()
+    fn part1_cost_to(&self, position: u64) -> u128 {
+        self.0.iter().map(|pos| pos.abs_diff(position) as u128).sum()
     }
 
-    fn part2_cost_to(&self, position: u64) -> u64 {
-        self.0.iter().map(|pos| (1..pos.abs_diff(position)+1).sum::<u64>()).sum()
+    fn part2_cost_to(&self, position: u64) -> u128 {
+        self.0.iter().map(|pos| triangular(pos.abs_diff(position))).sum()
     }
 
-    pub fn part1_cheapest_cost(&self) -> u64 {
-        (0..self.0.iter().max().unwrap()+1).map(|pos| self.part1_cost_to(pos)).min().unwrap()
+    pub fn cost_to_with<C: CostFunction>(&self, position: u64, cost: &C) -> u128 {
+        self.0.iter().map(|pos| cost.cost(pos.abs_diff(position))).sum()
     }
 
-    pub fn part2_cheapest_cost(&self) -> u64 {
-        (0..self.0.iter().max().unwrap()+1).map(|pos| self.part2_cost_to(pos)).min().unwrap()
+    pub fn cheapest_cost_with<C: CostFunction>(&self, cost: &C) -> u128 {
+        let max = *self.0.iter().max().unwrap();
+        minimize(0, max, cost.is_convex(), |pos| self.cost_to_with(pos, cost)).1
+    }
+
+    // Any median minimizes the sum of distances
+    pub fn part1_cheapest_cost(&self) -> u128 {
+        let mut positions = self.0.clone();
+        let middle = positions.len() / 2;
+        let (_, median, _) = positions.select_nth_unstable(middle);
+        self.part1_cost_to(*median)
+    }
+
+    // The real minimum lies within half a step of the mean, so the integer one is next to it
+    pub fn part2_cheapest_cost(&self) -> u128 {
+        let sum: u128 = self.0.iter().map(|pos| *pos as u128).sum();
+        let mean = (sum / self.0.len() as u128) as u64;
+        (mean.saturating_sub(1)..=mean.saturating_add(2)).map(|pos| self.part2_cost_to(pos)).min().unwrap()
     }
 }
 
@@ -40,9 +105,26 @@ pub mod tests {
         let crabs: Crabs = Crabs::from_str(input)?;
         assert_eq!(crabs.0, vec![0,0,5,5,5,8]);
 
+        let crabs: Crabs = Crabs::from_str("0, 0,5\n5 5\t8\n")?;
+        assert_eq!(crabs.0, vec![0,0,5,5,5,8]);
+
         Ok(())
     }
 
+    #[test]
+    fn malformed_input() {
+        assert_eq!(Crabs::from_str("").err(), Some(CrabError::EmptyInput));
+        assert_eq!(Crabs::from_str(" \n,").err(), Some(CrabError::EmptyInput));
+        assert!(matches!(Crabs::from_str("1,2,x3,4"), Err(CrabError::InvalidToken { offset: 4, ref token, source: _ }) if token == "x3"));
+        assert!(matches!(Crabs::from_str("1 -2"), Err(CrabError::InvalidToken { offset: 2, ref token, source: _ }) if token == "-2"));
+        assert_eq!(Crabs::from_str("1,,x").err(), Some(CrabError::EmptyField(2)));
+        assert_eq!(Crabs::from_str("1,2, \n").err(), Some(CrabError::EmptyField(4)));
+
+        let error = Crabs::from_str("1, 2,\n33x").err().unwrap();
+        assert_eq!(error.to_string(), "Invalid crab \"33x\" at byte 6");
+        assert_eq!(std::error::Error::source(&error).map(|source| source.to_string()), Some("Could not parse crab number".to_string()));
+    }
+
     #[test]
     fn part1_cost_to() -> Result<(), Box<dyn std::error::Error>> {
         let input = "16,1,2,0,4,2,7,1,2,14";
@@ -81,5 +163,45 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn cheapest_cost_matches_exhaustive() -> Result<(), Box<dyn std::error::Error>> {
+        for input in ["1", "0,0,5,5,5,8", "0,1,1000", "3,3,3,4", "0,100,101,102,103", "7,0,2,9,9,1,4"] {
+            let crabs: Crabs = Crabs::from_str(input)?;
+            let max = *crabs.0.iter().max().unwrap();
+            assert_eq!(crabs.part1_cheapest_cost(), (0..=max).map(|pos| crabs.part1_cost_to(pos)).min().unwrap());
+            assert_eq!(crabs.part2_cheapest_cost(), (0..=max).map(|pos| crabs.part2_cost_to(pos)).min().unwrap());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn cheapest_cost_with() -> Result<(), Box<dyn std::error::Error>> {
+        use cost::*;
+
+        let input = "16,1,2,0,4,2,7,1,2,14";
+        let crabs: Crabs = Crabs::from_str(input)?;
+        assert_eq!(crabs.cheapest_cost_with(&Linear), 37);
+        assert_eq!(crabs.cheapest_cost_with(&Triangular), 168);
+        assert_eq!(crabs.cheapest_cost_with(&Quadratic), (0..=16).map(|pos| crabs.cost_to_with(pos, &Quadratic)).min().unwrap());
+        assert_eq!(crabs.cheapest_cost_with(&Capped { cost: Linear, cap: 3 }), 15);
+        assert_eq!(crabs.cheapest_cost_with(&|d: u64| if d == 0 { 0 } else { 1 }), 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_positions() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs: Crabs = Crabs::from_str("0,1000000000000,1000000000000")?;
+        assert_eq!(crabs.part1_cheapest_cost(), 1_000_000_000_000);
+
+        // The total no longer fits in u64
+        let far: Crabs = Crabs::from_str(&format!("0,0,{max},{max},{max}", max = u64::MAX))?;
+        assert_eq!(far.part1_cheapest_cost(), 2 * u64::MAX as u128);
+        assert_eq!(crabs.part2_cost_to(666_666_666_667), crabs.part2_cheapest_cost());
+
+        Ok(())
+    }
+
 }
 
@@ -0,0 +1,153 @@
+use crate::craberror::CrabError;
+use crate::parse_list;
+use crate::cost::{minimize, CostFunction, Linear, Quadratic, Triangular};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CostModel {
+    Linear,
+    Triangular,
+    Quadratic,
+    // Linear fuel, but never more than the cap
+    Capped(u128),
+}
+
+impl FromStr for CostModel {
+    type Err = CrabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(CostModel::Linear),
+            "triangular" => Ok(CostModel::Triangular),
+            "quadratic" => Ok(CostModel::Quadratic),
+            _ => match s.strip_prefix("capped=") {
+                Some(cap) => Ok(CostModel::Capped(cap.parse()?)),
+                None => Err(CrabError::UnknownCostModel(s.to_string())),
+            },
+        }
+    }
+}
+
+impl CostFunction for CostModel {
+    fn cost(&self, distance: u64) -> u128 {
+        match self {
+            CostModel::Linear => Linear.cost(distance),
+            CostModel::Triangular => Triangular.cost(distance),
+            CostModel::Quadratic => Quadratic.cost(distance),
+            CostModel::Capped(cap) => Linear.cost(distance).min(*cap),
+        }
+    }
+
+    fn is_convex(&self) -> bool {
+        !matches!(self, CostModel::Capped(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crab {
+    pub position: u64,
+    pub model: CostModel,
+    pub weight: u64,
+}
+
+// Parses `position[:model[:weight]]`, a bare position is a linear crab of weight 1
+impl FromStr for Crab {
+    type Err = CrabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let position = parts.next().unwrap_or_default().parse()?;
+        let model = parts.next().map(CostModel::from_str).transpose()?.unwrap_or(CostModel::Linear);
+        let weight = parts.next().map(u64::from_str).transpose()?.unwrap_or(1);
+        if parts.next().is_some() {
+            return Err(CrabError::TrailingFields(s.to_string()));
+        }
+
+        Ok(Crab { position, model, weight })
+    }
+}
+
+impl Crab {
+    pub fn cost_to(&self, position: u64) -> u128 {
+        self.weight as u128 * self.model.cost(self.position.abs_diff(position))
+    }
+}
+
+// Crabs that each burn fuel their own way, e.g. "16:triangular:3,1,2:capped=5"
+pub struct MixedCrabs(Vec<Crab>);
+
+impl FromStr for MixedCrabs {
+    type Err = CrabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let crabs = parse_list(s, true, Crab::from_str)?;
+
+        Ok(MixedCrabs(crabs))
+    }
+}
+
+impl MixedCrabs {
+    pub fn crabs(&self) -> &[Crab] {
+        &self.0
+    }
+
+    pub fn cost_to(&self, position: u64) -> u128 {
+        self.0.iter().map(|crab| crab.cost_to(position)).sum()
+    }
+
+    // A weighted sum of convex costs is still convex, otherwise every position is tried
+    pub fn cheapest(&self) -> (u64, u128) {
+        let max = self.0.iter().map(|crab| crab.position).max().unwrap();
+        let convex = self.0.iter().all(|crab| crab.model.is_convex());
+        minimize(0, max, convex, |pos| self.cost_to(pos))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs = MixedCrabs::from_str("16:triangular:3,1,2:capped=5,0:quadratic")?;
+        assert_eq!(crabs.crabs(), &[
+            Crab { position: 16, model: CostModel::Triangular, weight: 3 },
+            Crab { position: 1, model: CostModel::Linear, weight: 1 },
+            Crab { position: 2, model: CostModel::Capped(5), weight: 1 },
+            Crab { position: 0, model: CostModel::Quadratic, weight: 1 },
+        ]);
+        assert_eq!(CostModel::from_str("cubic").err(), Some(CrabError::UnknownCostModel("cubic".to_string())));
+        assert_eq!(MixedCrabs::from_str("3 1:cubic").err(), Some(CrabError::InvalidToken {
+            offset: 2,
+            token: "1:cubic".to_string(),
+            source: Box::new(CrabError::UnknownCostModel("cubic".to_string())),
+        }));
+        assert!(MixedCrabs::from_str("1:linear:x").is_err());
+        assert_eq!(Crab::from_str("1:linear:2:junk"), Err(CrabError::TrailingFields("1:linear:2:junk".to_string())));
+        assert_eq!(MixedCrabs::from_str("\n").err(), Some(CrabError::EmptyInput));
+
+        Ok(())
+    }
+
+    #[test]
+    fn uniform_models_match_crabs() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "16,1,2,0,4,2,7,1,2,14";
+        assert_eq!(MixedCrabs::from_str(input)?.cheapest(), (2, 37));
+
+        let triangular = input.split(',').map(|pos| format!("{}:triangular", pos)).collect::<Vec<_>>().join(",");
+        assert_eq!(MixedCrabs::from_str(&triangular)?.cheapest(), (5, 168));
+
+        Ok(())
+    }
+
+    #[test]
+    fn cheapest_is_global() -> Result<(), Box<dyn std::error::Error>> {
+        for input in ["0:linear:5,10:triangular,20:quadratic:2", "0:capped=3,10:capped=3:4,20,3:triangular:2", "7:quadratic:0,9"] {
+            let crabs = MixedCrabs::from_str(input)?;
+            let exhaustive = (0..=20).map(|pos| crabs.cost_to(pos)).min().unwrap();
+            assert_eq!(crabs.cheapest().1, exhaustive);
+        }
+
+        Ok(())
+    }
+}
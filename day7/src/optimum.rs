@@ -0,0 +1,99 @@
+use crate::cost::{minimize, CostFunction};
+use crate::Crabs;
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Optimum {
+    pub cost: u128,
+    // Every position reaching `cost`, as ascending ranges
+    pub positions: Vec<RangeInclusive<u64>>,
+}
+
+fn ranges(positions: impl Iterator<Item = u64>) -> Vec<RangeInclusive<u64>> {
+    let mut ranges: Vec<RangeInclusive<u64>> = vec![];
+    for pos in positions {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == pos => *range = *range.start()..=pos,
+            _ => ranges.push(pos..=pos),
+        }
+    }
+    ranges
+}
+
+impl Crabs {
+    fn max_position(&self) -> u64 {
+        *self.0.iter().max().unwrap()
+    }
+
+    pub fn optimum_with<C: CostFunction>(&self, cost: &C) -> Optimum {
+        let max = self.max_position();
+        let cost_to = |pos| self.cost_to_with(pos, cost);
+        let (best, min) = minimize(0, max, cost.is_convex(), cost_to);
+
+        if !cost.is_convex() {
+            return Optimum { cost: min, positions: ranges((0..=max).filter(|pos| cost_to(*pos) == min)) };
+        }
+
+        // Ties of a convex cost are contiguous, so search both ends of the block around `best`
+        let (mut low, mut high) = (0, best);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if cost_to(mid) == min { high = mid } else { low = mid + 1 }
+        }
+        let start = low;
+
+        let (mut low, mut high) = (best, max);
+        while low < high {
+            let mid = high - (high - low) / 2;
+            if cost_to(mid) == min { low = mid } else { high = mid - 1 }
+        }
+
+        Optimum { cost: min, positions: vec![start..=low] }
+    }
+
+    // Writes `position,cost` rows for every position from 0 to the furthest crab
+    pub fn write_cost_curve<C: CostFunction, W: Write>(&self, mut out: W, cost: &C) -> std::io::Result<()> {
+        writeln!(out, "position,cost")?;
+        for pos in 0..=self.max_position() {
+            writeln!(out, "{},{}", pos, self.cost_to_with(pos, cost))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::cost::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn optimum() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs = Crabs::from_str("16,1,2,0,4,2,7,1,2,14")?;
+        assert_eq!(crabs.optimum_with(&Linear), Optimum { cost: 37, positions: vec![2..=2] });
+        assert_eq!(crabs.optimum_with(&Triangular), Optimum { cost: 168, positions: vec![5..=5] });
+
+        let crabs = Crabs::from_str("0,0,5,5,9,9")?;
+        assert_eq!(crabs.optimum_with(&Linear), Optimum { cost: 18, positions: vec![5..=5] });
+
+        let crabs = Crabs::from_str("1,8")?;
+        assert_eq!(crabs.optimum_with(&Linear), Optimum { cost: 7, positions: vec![1..=8] });
+
+        let crabs = Crabs::from_str("0,10")?;
+        let flat_far = |d: u64| if d <= 2 { 0 } else { 1 };
+        assert_eq!(crabs.optimum_with(&flat_far), Optimum { cost: 1, positions: vec![0..=2, 8..=10] });
+
+        Ok(())
+    }
+
+    #[test]
+    fn cost_curve() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs = Crabs::from_str("0,3")?;
+        let mut out = vec![];
+        crabs.write_cost_curve(&mut out, &Triangular)?;
+        assert_eq!(String::from_utf8(out)?, "position,cost\n0,6\n1,4\n2,4\n3,6\n");
+
+        Ok(())
+    }
+}
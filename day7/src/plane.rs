@@ -0,0 +1,147 @@
+use crate::craberror::CrabError;
+use crate::parse_list;
+use std::str::FromStr;
+
+// Crabs on a plane, parsed from whitespace separated `x,y` pairs. Never empty
+pub struct PlaneCrabs(Vec<(u64, u64)>);
+
+impl FromStr for PlaneCrabs {
+    type Err = CrabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let crabs = parse_list(s, false, |pair| {
+            let (x, y) = pair.split_once(',').ok_or(CrabError::NotAPair)?;
+            Ok::<_, CrabError>((x.parse()?, y.parse()?))
+        })?;
+
+        Ok(PlaneCrabs(crabs))
+    }
+}
+
+fn median(mut values: Vec<u64>) -> u64 {
+    let middle = values.len() / 2;
+    *values.select_nth_unstable(middle).1
+}
+
+impl PlaneCrabs {
+    pub fn manhattan_cost_to(&self, (x, y): (u64, u64)) -> u128 {
+        self.0.iter().map(|(cx, cy)| cx.abs_diff(x) as u128 + cy.abs_diff(y) as u128).sum()
+    }
+
+    pub fn euclidean_cost_to(&self, (x, y): (f64, f64)) -> f64 {
+        self.0.iter().map(|(cx, cy)| (*cx as f64 - x).hypot(*cy as f64 - y)).sum()
+    }
+
+    // Both axes are independent, so the median of each is exact
+    pub fn manhattan_meeting_point(&self) -> ((u64, u64), u128) {
+        let point = (median(self.0.iter().map(|c| c.0).collect()), median(self.0.iter().map(|c| c.1).collect()));
+        (point, self.manhattan_cost_to(point))
+    }
+
+    // Weiszfeld iteration from the centroid, until a step moves less than `tolerance`.
+    // An estimate closer than `tolerance` to a crab is moved onto it and handled as by Vardi and Zhang
+    pub fn euclidean_meeting_point(&self, tolerance: f64, max_iterations: usize) -> ((f64, f64), f64) {
+        let n = self.0.len() as f64;
+        let crabs = || self.0.iter().map(|(cx, cy)| (*cx as f64, *cy as f64));
+        let mut point = (crabs().map(|c| c.0).sum::<f64>() / n, crabs().map(|c| c.1).sum::<f64>() / n);
+
+        let mut converged = false;
+        for _ in 0..max_iterations {
+            let distance = |(cx, cy): (f64, f64), (px, py): (f64, f64)| (cx - px).hypot(cy - py);
+            let nearest = crabs().min_by(|a, b| distance(*a, point).total_cmp(&distance(*b, point))).unwrap();
+            let on_crab = distance(nearest, point) < tolerance;
+            if on_crab {
+                point = nearest;
+            }
+
+            // Crabs sharing the estimate's position pull in every direction, so they are only counted
+            let (mut x, mut y, mut weights, mut coinciding) = (0.0, 0.0, 0.0, 0.0);
+            for crab in crabs() {
+                let distance = distance(crab, point);
+                if distance == 0.0 {
+                    coinciding += 1.0;
+                    continue;
+                }
+                x += crab.0 / distance;
+                y += crab.1 / distance;
+                weights += 1.0 / distance;
+            }
+            if weights == 0.0 {
+                break;
+            }
+
+            let mut next = (x / weights, y / weights);
+            if on_crab {
+                // The crab is optimal when the pull of all other crabs can't move away from it
+                let pull = ((next.0 - point.0) * weights).hypot((next.1 - point.1) * weights);
+                if pull <= coinciding {
+                    break;
+                }
+                let stay = coinciding / pull;
+                next = ((1.0 - stay) * next.0 + stay * point.0, (1.0 - stay) * next.1 + stay * point.1);
+            } else if converged {
+                break;
+            }
+
+            converged = distance(next, point) < tolerance;
+            point = next;
+        }
+
+        (point, self.euclidean_cost_to(point))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs = PlaneCrabs::from_str("0,0 3,4\n10,2\n")?;
+        assert_eq!(crabs.0, vec![(0, 0), (3, 4), (10, 2)]);
+        assert_eq!(PlaneCrabs::from_str("1,2 3").err(), Some(CrabError::InvalidToken { offset: 4, token: "3".to_string(), source: Box::new(CrabError::NotAPair) }));
+        assert!(matches!(PlaneCrabs::from_str("1,2\n3,y"), Err(CrabError::InvalidToken { offset: 4, ref source, .. }) if matches!(**source, CrabError::ParseError(_))));
+        assert_eq!(PlaneCrabs::from_str(" ").err(), Some(CrabError::EmptyInput));
+
+        Ok(())
+    }
+
+    #[test]
+    fn manhattan() -> Result<(), Box<dyn std::error::Error>> {
+        let crabs = PlaneCrabs::from_str("0,0 3,4 10,2 1,9 2,2")?;
+        let (point, cost) = crabs.manhattan_meeting_point();
+        assert_eq!(point, (2, 2));
+        let exhaustive = (0..=10).flat_map(|x| (0..=9).map(move |y| (x, y))).map(|p| crabs.manhattan_cost_to(p)).min().unwrap();
+        assert_eq!(cost, exhaustive);
+
+        Ok(())
+    }
+
+    #[test]
+    fn euclidean() -> Result<(), Box<dyn std::error::Error>> {
+        // The Fermat point of a triangle with all angles below 120 degrees
+        let crabs = PlaneCrabs::from_str("0,0 2,0 1,2")?;
+        let ((x, y), cost) = crabs.euclidean_meeting_point(1e-9, 1000);
+        assert!((x - 1.0).abs() < 1e-6);
+        assert!((y - 1.0 / 3f64.sqrt()).abs() < 1e-6);
+        assert!((cost - (2.0 + 3f64.sqrt())).abs() < 1e-6);
+
+        // Beyond 120 degrees the obtuse corner itself is optimal
+        let crabs = PlaneCrabs::from_str("0,0 10,0 5,1")?;
+        let ((x, y), _) = crabs.euclidean_meeting_point(1e-9, 1000);
+        assert!((x - 5.0).abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
+
+        // Most crabs already share a spot, so nobody else is worth moving towards
+        let crabs = PlaneCrabs::from_str("0,0 0,0 0,0 0,0 0,0 10,0 0,10")?;
+        assert_eq!(crabs.euclidean_meeting_point(1e-9, 1000).0, (0.0, 0.0));
+
+        // A loose tolerance only ever snaps onto one crab position at a time
+        let crabs = PlaneCrabs::from_str("0,0 0,0 0,0 1,0 1,0 1,0 1,0")?;
+        assert_eq!(crabs.euclidean_meeting_point(2.0, 1000).0, (1.0, 0.0));
+
+        let crabs = PlaneCrabs::from_str("4,4 4,4")?;
+        assert_eq!(crabs.euclidean_meeting_point(1e-9, 1000), ((4.0, 4.0), 0.0));
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
+pub mod display;
 pub mod wire;
 
 use wire::*;
 use bimap::BiHashMap;
 use itertools::Itertools;
+use std::fmt;
 use std::str::FromStr;
 
 struct BrokenSevenSegmentMap(BiHashMap<wire::Wires, u8>);
@@ -20,8 +22,52 @@ impl BrokenSevenSegmentMap {
         Ok(*self.0.get_by_left(wires).ok_or(Part2Error::ValueNotFound)?)
     }
 
-    fn from_value(&self, value: u8) -> Option<wire::Wires> {
-        self.0.get_by_right(&value).cloned()
+    fn wires_of(&self, value: u8) -> Option<wire::Wires> {
+        self.0.get_by_right(&value).copied()
+    }
+}
+
+// Physical segment driven by each scrambled wire
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WirePermutation([Wire; 7]);
+
+impl WirePermutation {
+    pub fn segment(&self, wire: Wire) -> Wire {
+        self.0[wire.index()]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Wire, Wire)> + '_ {
+        Wire::all().zip(self.0.iter().copied())
+    }
+}
+
+impl fmt::Display for WirePermutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let to_write = self.iter().map(|(wire, segment)| format!("{}->{}", wire, segment)).join(" ");
+        write!(f, "{}", to_write)
+    }
+}
+
+// Digits using the wire, as a bitmask. Each segment of the standard digits has a different one
+fn digit_signature(wire: Wire, digit_wires: impl Fn(u8) -> Result<Wires, Part2Error>) -> Result<u16, Part2Error> {
+    (0..10).try_fold(0, |signature, digit| Ok(signature | (u16::from(digit_wires(digit)?.contains(wire)) << digit)))
+}
+
+impl BrokenSevenSegmentMap {
+    fn permutation(&self) -> Result<WirePermutation, Part2Error> {
+        let mut segments = Wire::all().map(|segment| {
+            digit_signature(segment, |digit| wire::Wires::from_str(display::DIGITS[digit as usize].1)).map(|signature| (signature, segment))
+        }).collect::<Result<Vec<_>, _>>()?;
+        segments.sort();
+
+        let mut permutation = [Wire::try_from('a')?; 7];
+        for wire in Wire::all() {
+            let signature = digit_signature(wire, |digit| self.wires_of(digit).ok_or(Part2Error::ValueNotFound))?;
+            let found = segments.binary_search_by_key(&signature, |(signature, _)| *signature).or(Err(Part2Error::ValueNotFound))?;
+            permutation[wire.index()] = segments[found].1;
+        }
+
+        Ok(WirePermutation(permutation))
     }
 }
 
@@ -30,10 +76,10 @@ pub struct SegmentSolver {}
 impl SegmentSolver {
     fn create_map(left: &str) -> Result<BrokenSevenSegmentMap, Part2Error> {
         let mut map = BrokenSevenSegmentMap::new();
+        let patterns = left.split_whitespace().map(wire::Wires::from_str).collect::<Result<Vec<_>, _>>()?;
 
         // Find 1, 4, 7 and 8
-        for wires in left.trim().split(' ').map(wire::Wires::from_str) {
-            let wires = wires?;
+        for &wires in &patterns {
             match wires.len() {
                 2 => map.add_new_map(wires, 1)?,
                 4 => map.add_new_map(wires, 4)?,
@@ -44,21 +90,20 @@ impl SegmentSolver {
         }
 
         // Find 6 and 9
-        for wires in left.trim().split(' ').map(wire::Wires::from_str) {
-            let wires = wires?;
+        for &wires in &patterns {
             if map.decode(&wires).is_err() {
-                let four: Wires = map.from_value(4).ok_or(Part2Error::ValueNotFound)?;
-                let seven: Wires = map.from_value(7).ok_or(Part2Error::ValueNotFound)?;
-                let eight: Wires = map.from_value(8).ok_or(Part2Error::ValueNotFound)?;
+                let four: Wires = map.wires_of(4).ok_or(Part2Error::ValueNotFound)?;
+                let seven: Wires = map.wires_of(7).ok_or(Part2Error::ValueNotFound)?;
+                let eight: Wires = map.wires_of(8).ok_or(Part2Error::ValueNotFound)?;
 
                 // Find 6
-                if (wires.clone() + seven) == eight {
+                if (wires + seven) == eight {
                     map.add_new_map(wires, 6)?;
                     continue;
                 }
 
                 // Find 9
-                if (wires.clone() + four) == wires {
+                if (wires + four) == wires {
                     map.add_new_map(wires, 9)?;
                     continue;
                 }
@@ -66,13 +111,12 @@ impl SegmentSolver {
         }
 
         // Find 5
-        for wires in left.trim().split(' ').map(wire::Wires::from_str) {
-            let wires = wires?;
+        for &wires in &patterns {
             if map.decode(&wires).is_err() {
-                let six: Wires = map.from_value(6).ok_or(Part2Error::ValueNotFound)?;
+                let six: Wires = map.wires_of(6).ok_or(Part2Error::ValueNotFound)?;
 
                 // Find 5
-                if (wires.clone() - six) == Wires::empty() {
+                if (wires - six) == Wires::empty() {
                     map.add_new_map(wires, 5)?;
                     continue;
                 }
@@ -80,13 +124,12 @@ impl SegmentSolver {
         }
 
         // Find 3
-        for wires in left.trim().split(' ').map(wire::Wires::from_str) {
-            let wires = wires?;
+        for &wires in &patterns {
             if map.decode(&wires).is_err() {
-                let nine: Wires = map.from_value(9).ok_or(Part2Error::ValueNotFound)?;
+                let nine: Wires = map.wires_of(9).ok_or(Part2Error::ValueNotFound)?;
 
                 // Find 3
-                if (wires.clone() - nine) == Wires::empty() {
+                if (wires - nine) == Wires::empty() {
                     map.add_new_map(wires, 3)?;
                     continue;
                 }
@@ -94,13 +137,12 @@ impl SegmentSolver {
         }
 
         // Find 2 and 0
-        for wires in left.trim().split(' ').map(wire::Wires::from_str) {
-            let wires = wires?;
+        for &wires in &patterns {
             if map.decode(&wires).is_err() {
-                let three: Wires = map.from_value(3).ok_or(Part2Error::ValueNotFound)?;
+                let three: Wires = map.wires_of(3).ok_or(Part2Error::ValueNotFound)?;
 
                 // Find 2
-                if (wires.clone() - three).len() == 1 {
+                if (wires - three).len() == 1 {
                     map.add_new_map(wires, 2)?;
                     continue;
                 } else { // Find 0
@@ -113,11 +155,17 @@ impl SegmentSolver {
         Ok(map)
     }
 
+    // Which segment every wire of the line is connected to
+    pub fn solve_permutation(line: &str) -> Result<WirePermutation, Part2Error> {
+        let map_input = line.split('|').next().unwrap_or_default();
+        SegmentSolver::create_map(map_input)?.permutation()
+    }
+
     pub fn solve_segments(line: &str) -> Result<u64, Part2Error> {
         let (map_input, seg_input) = line.split('|').collect_tuple().ok_or(Part2Error::ParseError("Couldn't parse input line".to_string()))?;
         let map = SegmentSolver::create_map(map_input)?;
         let mut result = 0;
-        for (power, wire) in seg_input.trim().split(' ').rev().enumerate() {
+        for (power, wire) in seg_input.split_whitespace().rev().enumerate() {
             let x: u64 = map.decode(&wire::Wires::from_str(wire)?)?.into();
             result += 10_u64.pow(power.try_into().unwrap()) * x;
         }
@@ -206,4 +254,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn solve_permutation() -> Result<(), Box<dyn std::error::Error>> {
+        let permutation = SegmentSolver::solve_permutation("abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg abcdfg")?;
+        assert_eq!(permutation.to_string(), "a->a b->b c->c d->d e->e f->f g->g");
+
+        let permutation = SegmentSolver::solve_permutation("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf")?;
+        assert_eq!(permutation.segment(Wire::try_from('d')?), Wire::try_from('a')?);
+        assert_eq!(permutation.to_string(), "a->c b->f c->g d->a e->b f->d g->e");
+
+        Ok(())
+    }
 }
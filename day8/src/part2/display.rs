@@ -0,0 +1,304 @@
+use crate::part2::wire::Part2Error;
+use std::fmt;
+
+// Named like the puzzle: a on top, b and c upper left and right, d in the middle,
+// e and f lower left and right, g at the bottom
+pub(crate) const DIGITS: [(char, &str); 10] = [
+    ('0', "abcefg"), ('1', "cf"), ('2', "acdeg"), ('3', "acdfg"), ('4', "bcdf"),
+    ('5', "abdfg"), ('6', "abdefg"), ('7', "acf"), ('8', "abcdefg"), ('9', "abcdfg"),
+];
+
+const HEX_LETTERS: [(char, &str); 6] = [
+    ('A', "abcdef"), ('b', "bdefg"), ('C', "abeg"), ('d', "cdefg"), ('E', "abdeg"), ('F', "abde"),
+];
+
+// a-f run clockwise from the top, g and h are the middle halves,
+// i, j and k the upper diagonals and center, l, m and n the lower ones
+const ALPHANUMERIC: [(char, &str); 36] = [
+    ('0', "abcdefkl"), ('1', "bck"), ('2', "abdegh"), ('3', "abcdh"), ('4', "bcfgh"),
+    ('5', "acdfgh"), ('6', "acdefgh"), ('7', "abc"), ('8', "abcdefgh"), ('9', "abcdfgh"),
+    ('A', "abcefgh"), ('B', "abcdhjm"), ('C', "adef"), ('D', "abcdjm"), ('E', "adefg"),
+    ('F', "aefg"), ('G', "acdefh"), ('H', "bcefgh"), ('I', "adjm"), ('J', "bcde"),
+    ('K', "efgkn"), ('L', "def"), ('M', "bcefik"), ('N', "bcefin"), ('O', "abcdef"),
+    ('P', "abefgh"), ('Q', "abcdefn"), ('R', "abefghn"), ('S', "acdhi"), ('T', "ajm"),
+    ('U', "bcdef"), ('V', "efkl"), ('W', "bcefln"), ('X', "ikln"), ('Y', "ikm"),
+    ('Z', "adkl"),
+];
+
+// Named segments and the glyphs they can show, with 1 to 32 segments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentDisplay {
+    segments: Vec<char>,
+    glyphs: Vec<(char, u32)>,
+}
+
+impl SegmentDisplay {
+    pub fn new(segments: &str, glyphs: &[(char, &str)]) -> Result<Self, Part2Error> {
+        let segments: Vec<char> = segments.chars().collect();
+        if segments.is_empty() || segments.len() > 32 {
+            return Err(Part2Error::ParseError("Displays need from 1 to 32 segments".to_string()));
+        }
+        if segments.iter().enumerate().any(|(i, segment)| segments[..i].contains(segment)) {
+            return Err(Part2Error::DoubleEntry);
+        }
+
+        let mut display = SegmentDisplay { segments, glyphs: vec![] };
+        for (name, pattern) in glyphs {
+            let mask = display.mask(pattern)?;
+            if display.glyphs.iter().any(|(other, other_mask)| other == name || *other_mask == mask) {
+                return Err(Part2Error::DoubleEntry);
+            }
+            display.glyphs.push((*name, mask));
+        }
+
+        Ok(display)
+    }
+
+    pub fn seven_segment() -> Self {
+        SegmentDisplay::new("abcdefg", &DIGITS).unwrap()
+    }
+
+    pub fn seven_segment_hex() -> Self {
+        SegmentDisplay::new("abcdefg", &[&DIGITS[..], &HEX_LETTERS[..]].concat()).unwrap()
+    }
+
+    pub fn fourteen_segment() -> Self {
+        SegmentDisplay::new("abcdefghijklmn", &ALPHANUMERIC).unwrap()
+    }
+
+    fn full(&self) -> u32 {
+        u32::MAX >> (32 - self.segments.len())
+    }
+
+    fn mask(&self, pattern: &str) -> Result<u32, Part2Error> {
+        pattern.chars().try_fold(0, |mask, c| match self.segments.iter().position(|segment| *segment == c) {
+            Some(index) => Ok(mask | 1 << index),
+            None => Err(Part2Error::ParseError(format!("Unknown segment {}", c))),
+        })
+    }
+
+    // Finds the only wiring that turns every observed pattern into a glyph
+    pub fn solve(&self, patterns: &[&str]) -> Result<Wiring<'_>, Part2Error> {
+        let mut masks = patterns.iter().map(|pattern| self.mask(pattern)).collect::<Result<Vec<_>, _>>()?;
+        masks.sort_unstable();
+        masks.dedup();
+
+        let candidates = masks.iter()
+            .map(|mask| (0..self.glyphs.len()).filter(|glyph| self.glyphs[*glyph].1.count_ones() == mask.count_ones()).collect())
+            .collect();
+        let state = State { domains: vec![self.full(); self.segments.len()], candidates };
+
+        let mut solutions = vec![];
+        self.search(&masks, state, &mut solutions);
+        match solutions.len() {
+            0 => Err(Part2Error::NoSolution),
+            1 => Ok(Wiring { display: self, permutation: solutions.remove(0) }),
+            _ => Err(Part2Error::Ambiguous),
+        }
+    }
+
+    // Stops as soon as a second solution shows the wiring is ambiguous
+    fn search(&self, patterns: &[u32], mut state: State, solutions: &mut Vec<Vec<usize>>) {
+        if !self.propagate(patterns, &mut state) {
+            return;
+        }
+
+        let open = (0..state.domains.len())
+            .filter(|wire| state.domains[*wire].count_ones() > 1)
+            .min_by_key(|wire| state.domains[*wire].count_ones());
+        let wire = match open {
+            Some(wire) => wire,
+            None => {
+                solutions.push(state.domains.iter().map(|domain| domain.trailing_zeros() as usize).collect());
+                return;
+            }
+        };
+
+        for segment in 0..self.segments.len() {
+            if state.domains[wire] & 1 << segment != 0 && solutions.len() < 2 {
+                let mut branch = state.clone();
+                branch.domains[wire] = 1 << segment;
+                self.search(patterns, branch, solutions);
+            }
+        }
+    }
+
+    // Narrows domains and candidates until nothing changes, false once a contradiction shows up
+    fn propagate(&self, patterns: &[u32], state: &mut State) -> bool {
+        let full = self.full();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (pattern, candidates) in patterns.iter().zip(state.candidates.iter_mut()) {
+                let domains = &state.domains;
+                let fits = |glyph: u32| (0..domains.len()).all(|wire| match pattern & 1 << wire {
+                    0 => domains[wire] & !glyph & full != 0,
+                    _ => domains[wire] & glyph != 0,
+                });
+                let before = candidates.len();
+                candidates.retain(|glyph| fits(self.glyphs[*glyph].1));
+                changed |= candidates.len() != before;
+                if candidates.is_empty() {
+                    return false;
+                }
+
+                let lit = candidates.iter().fold(0, |lit, glyph| lit | self.glyphs[*glyph].1);
+                let dark = candidates.iter().fold(0, |dark, glyph| dark | (!self.glyphs[*glyph].1 & full));
+                for (wire, domain) in state.domains.iter_mut().enumerate() {
+                    let allowed = if pattern & 1 << wire != 0 { lit } else { dark };
+                    changed |= *domain & !allowed != 0;
+                    *domain &= allowed;
+                }
+            }
+
+            // Every wire drives its own segment and every pattern shows its own glyph
+            for wire in 0..state.domains.len() {
+                let domain = state.domains[wire];
+                if domain == 0 {
+                    return false;
+                }
+                if domain.count_ones() == 1 {
+                    for (other, other_domain) in state.domains.iter_mut().enumerate() {
+                        if other != wire && *other_domain & domain != 0 {
+                            *other_domain &= !domain;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            for pattern in 0..state.candidates.len() {
+                if let [glyph] = state.candidates[pattern][..] {
+                    for (other, candidates) in state.candidates.iter_mut().enumerate() {
+                        if other != pattern && candidates.contains(&glyph) {
+                            candidates.retain(|candidate| *candidate != glyph);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Clone)]
+struct State {
+    // Segments each wire may still drive
+    domains: Vec<u32>,
+    // Glyphs each pattern may still show
+    candidates: Vec<Vec<usize>>,
+}
+
+// Segment driven by every wire of a scrambled display
+#[derive(Debug)]
+pub struct Wiring<'a> {
+    display: &'a SegmentDisplay,
+    permutation: Vec<usize>,
+}
+
+impl Wiring<'_> {
+    pub fn segment(&self, wire: char) -> Option<char> {
+        let index = self.display.segments.iter().position(|segment| *segment == wire)?;
+        Some(self.display.segments[self.permutation[index]])
+    }
+
+    pub fn decode(&self, pattern: &str) -> Result<char, Part2Error> {
+        let wires = self.display.mask(pattern)?;
+        let mask = self.permutation.iter().enumerate()
+            .filter(|(wire, _)| wires & 1 << wire != 0)
+            .fold(0, |mask, (_, segment)| mask | 1 << segment);
+
+        self.display.glyphs.iter().find(|(_, glyph)| *glyph == mask).map(|(name, _)| *name).ok_or(Part2Error::ValueNotFound)
+    }
+}
+
+impl fmt::Display for Wiring<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = &self.display.segments;
+        let to_write = self.permutation.iter().enumerate()
+            .map(|(wire, segment)| format!("{}->{}", segments[wire], segments[*segment]))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}", to_write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part2::SegmentSolver;
+
+    // Rewires every glyph, `wires` holds the wire connected to each segment
+    fn scramble(display: &SegmentDisplay, wires: &str) -> Vec<String> {
+        let wires: Vec<char> = wires.chars().collect();
+        display.glyphs.iter()
+            .map(|(_, mask)| (0..wires.len()).filter(|segment| mask & 1 << segment != 0).map(|segment| wires[segment]).collect())
+            .collect()
+    }
+
+    fn assert_unscrambles(display: &SegmentDisplay, wires: &str) -> Result<(), Part2Error> {
+        let patterns = scramble(display, wires);
+        let wiring = display.solve(&patterns.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+        for (wire, segment) in wires.chars().zip(display.segments.iter()) {
+            assert_eq!(wiring.segment(wire), Some(*segment));
+        }
+        for (pattern, (name, _)) in patterns.iter().zip(display.glyphs.iter()) {
+            assert_eq!(wiring.decode(pattern)?, *name);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn seven_segment() -> Result<(), Box<dyn std::error::Error>> {
+        let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let display = SegmentDisplay::seven_segment();
+        let wiring = display.solve(&line.split(' ').collect::<Vec<_>>())?;
+
+        assert_eq!(wiring.to_string(), SegmentSolver::solve_permutation(line)?.to_string());
+        assert_eq!("cdfeb fcadb cdfeb cdbaf".split(' ').map(|pattern| wiring.decode(pattern)).collect::<Result<String, _>>()?, "5353");
+
+        Ok(())
+    }
+
+    #[test]
+    fn seven_segment_hex() -> Result<(), Box<dyn std::error::Error>> {
+        assert_unscrambles(&SegmentDisplay::seven_segment_hex(), "gfdaceb")?;
+        assert_unscrambles(&SegmentDisplay::seven_segment_hex(), "abcdefg")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fourteen_segment() -> Result<(), Box<dyn std::error::Error>> {
+        assert_unscrambles(&SegmentDisplay::fourteen_segment(), "dgkanbchlmiejf")?;
+        assert_unscrambles(&SegmentDisplay::fourteen_segment(), "nmlkjihgfedcba")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_display() -> Result<(), Box<dyn std::error::Error>> {
+        let display = SegmentDisplay::new("xyz", &[('<', "x"), ('-', "xy"), ('=', "xyz")])?;
+        assert_unscrambles(&display, "zxy")?;
+
+        assert_eq!(display.solve(&["y"]).err(), Some(Part2Error::Ambiguous));
+        assert_eq!(display.solve(&["x", "y"]).err(), Some(Part2Error::NoSolution));
+        assert!(display.solve(&["xw"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_display() {
+        assert_eq!(SegmentDisplay::new("aba", &[]), Err(Part2Error::DoubleEntry));
+        assert!(SegmentDisplay::new("", &[]).is_err());
+        assert!(SegmentDisplay::new(&('A'..='z').take(33).collect::<String>(), &[]).is_err());
+        assert_eq!(SegmentDisplay::new("ab", &[('1', "a"), ('2', "a")]), Err(Part2Error::DoubleEntry));
+        assert!(SegmentDisplay::new("ab", &[('1', "c")]).is_err());
+    }
+}
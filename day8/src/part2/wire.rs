@@ -12,6 +12,10 @@ pub enum Part2Error {
     DoubleEntry,
     #[error("Value not found")]
     ValueNotFound,
+    #[error("No wiring shows these patterns")]
+    NoSolution,
+    #[error("More than one wiring shows these patterns")]
+    Ambiguous,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
@@ -28,16 +32,54 @@ impl TryFrom<char> for Wire {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug)]
-pub struct Wires(Vec<Wire>);
+impl Wire {
+    pub fn all() -> impl Iterator<Item = Wire> {
+        ('a'..='g').map(Wire)
+    }
+
+    // Position of the wire, a is 0
+    pub fn index(self) -> usize {
+        (self.0 as u8 - b'a') as usize
+    }
+
+    fn bit(self) -> u8 {
+        1 << self.index()
+    }
+}
+
+impl fmt::Display for Wire {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// One bit per wire, a in the lowest bit
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Wires(u8);
 
 impl Wires {
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
     }
 
     pub fn empty() -> Wires {
-        Wires(vec![])
+        Wires(0)
+    }
+
+    pub fn contains(&self, wire: Wire) -> bool {
+        self.0 & wire.bit() != 0
+    }
+
+    pub fn is_subset(&self, other: Wires) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Wire> + '_ {
+        Wire::all().filter(|wire| self.contains(*wire))
     }
 }
 
@@ -45,17 +87,13 @@ impl FromStr for Wires {
     type Err = Part2Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut wires = s.chars().map(Wire::try_from).collect::<Result<Vec<_>, _>>()?;
-        wires.sort();
-        wires.dedup();
-
-        Ok(Wires(wires))
+        s.chars().map(Wire::try_from).try_fold(Wires::empty(), |wires, wire| Ok(wires + wire?))
     }
 }
 
 impl fmt::Display for Wires {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let to_write: String = self.0.iter().map(|ch| ch.0).collect();
+        let to_write: String = self.iter().map(|ch| ch.0).collect();
         write!(f, "{}", to_write)
     }
 }
@@ -63,12 +101,10 @@ impl fmt::Display for Wires {
 impl Add<Wires> for Wires {
     type Output = Self;
 
+    // Union of the wire sets
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn add(self, rhs: Self) -> Self::Output {
-        let mut wires = self.0.into_iter().chain(rhs.0.into_iter()).collect::<Vec<_>>();
-        wires.sort();
-        wires.dedup();
-
-        Wires(wires)
+        Wires(self.0 | rhs.0)
     }
 }
 
@@ -76,22 +112,17 @@ impl Sub<Wires> for Wires {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let mut ret = self;
-        ret.0.retain(|elem| !rhs.0.contains(elem));
-        ret
+        Wires(self.0 & !rhs.0)
     }
 }
 
 impl Add<Wire> for Wires {
     type Output = Self;
 
+    // Union of the wire sets
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn add(self, rhs: Wire) -> Self::Output {
-        let mut ret = self;
-        ret.0.push(rhs);
-        ret.0.sort();
-        ret.0.dedup();
-
-        ret
+        Wires(self.0 | rhs.bit())
     }
 }
 
@@ -99,9 +130,7 @@ impl Sub<Wire> for Wires {
     type Output = Self;
 
     fn sub(self, rhs: Wire) -> Self::Output {
-        let mut ret = self;
-        ret.0.retain(|elem| elem != &rhs);
-        ret
+        Wires(self.0 & !rhs.bit())
     }
 }
 
@@ -130,7 +159,7 @@ mod tests {
     #[test]
     fn wires_from() -> Result<(), Box<dyn std::error::Error>> {
         let wires = Wires::from_str("abcdefg")?;
-        assert_eq!(wires.0.iter().map(|w| w.0).collect::<String>(), "abcdefg");
+        assert_eq!(wires.to_string(), "abcdefg");
 
         Ok(())
     }
@@ -139,6 +168,7 @@ mod tests {
     fn wires_from_empty() -> Result<(), Box<dyn std::error::Error>> {
         let wires = Wires::from_str("")?;
         assert_eq!(wires.len(), 0);
+        assert!(wires.is_empty());
 
         Ok(())
     }
@@ -146,7 +176,7 @@ mod tests {
     #[test]
     fn wires_from_sorted() -> Result<(), Box<dyn std::error::Error>> {
         let wires = Wires::from_str("cafbdeg")?;
-        assert_eq!(wires.0.iter().map(|w| w.0).collect::<String>(), "abcdefg");
+        assert_eq!(wires.to_string(), "abcdefg");
 
         Ok(())
     }
@@ -154,7 +184,19 @@ mod tests {
     #[test]
     fn wires_from_duplicated() -> Result<(), Box<dyn std::error::Error>> {
         let wires = Wires::from_str("bccad")?;
-        assert_eq!(wires.0.iter().map(|w| w.0).collect::<String>(), "abcd");
+        assert_eq!(wires.to_string(), "abcd");
+
+        Ok(())
+    }
+
+    #[test]
+    fn wires_contains() -> Result<(), Box<dyn std::error::Error>> {
+        let wires = Wires::from_str("bdg")?;
+        assert!(wires.contains(Wire::try_from('d')?));
+        assert!(!wires.contains(Wire::try_from('a')?));
+        assert!(wires.is_subset(Wires::from_str("abdfg")?));
+        assert!(!wires.is_subset(Wires::from_str("abdf")?));
+        assert_eq!(wires.iter().collect::<Vec<_>>(), vec![Wire('b'), Wire('d'), Wire('g')]);
 
         Ok(())
     }